@@ -0,0 +1,128 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use opus_rs::packet::coder::PacketBuilder;
+use opus_rs::packet::parser::{parse, Code, FrameConfig};
+
+/// CELT-only full-band 2.5 ms mono, allowing upto 48 frames a packet.
+const TOC_CELT_2_5MS: u8 = 28 << 3;
+/// SILK-only wide-band 20 ms mono.
+const TOC_SILK_20MS: u8 = 9 << 3;
+
+fn build(toc: u8, frames: &[&[u8]], code: Code, is_vbr: bool) -> Vec<u8> {
+    PacketBuilder::new(FrameConfig::from_toc(toc))
+        .frames(frames)
+        .code(code)
+        .vbr(is_vbr)
+        .build()
+        .unwrap()
+}
+
+fn bench_codes(c: &mut Criterion) {
+    let frame = [0x55; 120];
+    let frames: Vec<&[u8]> = (0..48).map(|i| &frame[..if i % 2 == 0 { 120 } else { 60 }]).collect();
+
+    let packets = [
+        ("code0", build(TOC_SILK_20MS, &[&frame], Code::Code0, false)),
+        ("code1", build(TOC_SILK_20MS, &[&frame, &frame], Code::Code1, false)),
+        ("code2", build(TOC_SILK_20MS, &frames[..2], Code::Code2, false)),
+        ("code3_cbr_20", build(TOC_CELT_2_5MS, &[&frame[..]; 20], Code::Code3, false)),
+        ("code3_cbr_48", build(TOC_CELT_2_5MS, &[&frame[..]; 48], Code::Code3, false)),
+        ("code3_vbr_48", build(TOC_CELT_2_5MS, &frames, Code::Code3, true)),
+    ];
+
+    let mut group = c.benchmark_group("parse");
+    let mut out = Vec::with_capacity(48);
+
+    // time is per packet, throughput in bytes of packets parsed.
+    for (name, packet) in &packets {
+        group.throughput(Throughput::Bytes(packet.len() as u64));
+        group.bench_function(*name, |b| b.iter(|| {
+            out.clear();
+            parse(&mut out, black_box(packet)).unwrap().info
+        }));
+    }
+
+    group.finish();
+}
+
+/// Ogg page carrying whole `packets`, the CRC is left unset as it goes unchecked.
+#[cfg(feature = "ogg")]
+fn ogg_page(header_type: u8, seq: u32, packets: &[Vec<u8>]) -> Vec<u8> {
+    let mut page = Vec::new();
+
+    page.extend_from_slice(b"OggS");
+    page.extend_from_slice(&[0, header_type]);
+    page.extend_from_slice(&0u64.to_le_bytes());
+    page.extend_from_slice(&1u32.to_le_bytes());
+    page.extend_from_slice(&seq.to_le_bytes());
+    page.extend_from_slice(&0u32.to_le_bytes());
+
+    let mut lacing = Vec::new();
+
+    for packet in packets {
+        lacing.extend(std::iter::repeat_n(255, packet.len() / 255));
+        lacing.push((packet.len() % 255) as u8);
+    }
+
+    page.push(lacing.len() as u8);
+    page.extend_from_slice(&lacing);
+
+    for packet in packets {
+        page.extend_from_slice(packet);
+    }
+
+    page
+}
+
+#[cfg(feature = "ogg")]
+fn bench_ogg(c: &mut Criterion) {
+    use opus_rs::ogg::OggReader;
+
+    let mut head = b"OpusHead".to_vec();
+    head.extend_from_slice(&[1, 2, 0x38, 0x01, 0x80, 0xbb, 0, 0, 0, 0, 0]);
+
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes());
+
+    // a mix of all the codes, as encoders switch between them.
+    let frame = [0x55; 80];
+    let packets = [
+        build(TOC_SILK_20MS, &[&frame], Code::Code0, false),
+        build(TOC_SILK_20MS, &[&frame, &frame], Code::Code1, false),
+        build(TOC_SILK_20MS, &[&frame, &frame[..40]], Code::Code2, false),
+        build(TOC_CELT_2_5MS, &[&frame[..]; 8], Code::Code3, false),
+        build(TOC_CELT_2_5MS, &[&frame[..], &frame[..20], &frame[..60]], Code::Code3, true),
+    ];
+
+    let mut stream = ogg_page(0x02, 0, &[head]);
+    stream.extend(ogg_page(0, 1, &[tags]));
+
+    for seq in 2..50 {
+        stream.extend(ogg_page(0, seq, &packets));
+    }
+
+    let mut group = c.benchmark_group("ogg");
+
+    group.throughput(Throughput::Bytes(stream.len() as u64));
+    group.bench_function("mixed", |b| b.iter(|| {
+        let mut num_frames = 0;
+
+        for packet in OggReader::new(black_box(&stream[..])).unwrap() {
+            let packet = packet.unwrap();
+            let mut frames = Vec::with_capacity(48);
+
+            num_frames += parse(&mut frames, &packet).unwrap().info.num_frames;
+        }
+
+        num_frames
+    }));
+
+    group.finish();
+}
+
+#[cfg(feature = "ogg")]
+criterion_group!(benches, bench_codes, bench_ogg);
+#[cfg(not(feature = "ogg"))]
+criterion_group!(benches, bench_codes);
+criterion_main!(benches);
@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use opus_rs::packet::parser::*;
+
+// any input must be either parsed or rejected, never panic.
+fuzz_target!(|packet: &[u8]| {
+    let lenient = ParseOptions::builder().validate_padding(true).build();
+
+    let _ = parse(&mut Vec::new(), packet);
+    let _ = parse_with_options(&mut Vec::new(), packet, &lenient);
+    let _ = parse_self_delimiting(&mut Vec::new(), packet);
+
+    if let Ok(mut frames) = FrameIter::with_options(packet, &lenient) {
+        let _ = frames.padding();
+
+        for _ in frames.by_ref() {}
+    }
+});
@@ -1,16 +1,59 @@
+use std::collections::HashMap;
 use std::env;
+use std::error::Error;
+use std::process;
 
 use ffmpeg_next::format;
 use ffmpeg_next::codec;
 
-mod utils;
+use opus_rs::packet::parser::{FrameIter, Info};
+
+/// Totals of the packets of an [`Info`], for `--summary`.
+#[derive(Default)]
+struct Totals {
+    packets: u64,
+    frames: u64,
+    bytes: u64,
+    duration_us: u64,
+}
+
+impl Totals {
+    fn add(&mut self, other: &Totals) {
+        self.packets += other.packets;
+        self.frames += other.frames;
+        self.bytes += other.bytes;
+        self.duration_us += other.duration_us;
+    }
+
+    fn bitrate_bps(&self) -> u64 {
+        match self.duration_us {
+            0 => 0,
+            duration_us => self.bytes * 8 * 1_000_000 / duration_us
+        }
+    }
+}
 
 fn main() {
-    let input_file = env::args()
-    .nth(1)
-    .expect("Input file unspecified");
+    if let Err(err) = run() {
+        eprintln!("opusstat: {}", err);
+        process::exit(1);
+    }
+}
 
-    let mut input = format::input(&input_file).unwrap();
+fn run() -> Result<(), Box<dyn Error>> {
+    let (flags, args): (Vec<_>, Vec<_>) = env::args()
+    .skip(1)
+    .partition(|arg| arg.starts_with("--"));
+
+    let dump_frames = flags.iter().any(|flag| flag == "--frames");
+    let summary = flags.iter().any(|flag| flag == "--summary");
+
+    let input_file = args
+    .into_iter()
+    .next()
+    .ok_or("Input file unspecified")?;
+
+    let mut input = format::input(&input_file)?;
 
     let packets = input
     .packets()
@@ -18,36 +61,56 @@ fn main() {
 
     let mut last_info = None;
     let mut num_same_conf = 0;
-    let mut frames = Vec::<_>::new();
+    let mut configs: HashMap<Info, Totals> = HashMap::new();
 
     for (stream, packet) in packets {
-        let mut frames_scope = utils::VecScope::new(&mut frames);
-
-        let internal = opus_rs::packet::parser::parse(
-            &mut frames_scope, 
-            packet.data().unwrap()).unwrap();
-        let info = internal.info;
-
-        if last_info != Some(info) || last_info == None {
-            println!("s={} mode={:?} bwidth={:?} dur={:?}ms nframes={:?} code={:?} vbr?={} stereo?={} \
-                      pad={:?}", 
-
-            stream.id(),
-            info.frame_config.config.mode,
-            info.frame_config.config.bandwith,
-            info.frame_config.config.framesize,
-            info.num_frames,
-            info.code_no as usize,
-            match info.is_vbr {
-                Some(v) => v.to_string(),
-                None => "?".to_string()
-            }, 
-            info.frame_config.is_stereo,
-            match internal.padding {
-                Some(p) => p.0,
-                None => 0
+        // e.g. flush packets carry no data.
+        let data = match packet.data() {
+            Some(data) => data,
+            None => continue
+        };
+
+        let mut frames = match FrameIter::new(data) {
+            Ok(frames) => frames,
+            Err(err) => {
+                eprintln!("opusstat: s={}: {}", stream.id(), err);
+                continue;
+            }
+        };
+
+        let padding = frames.padding();
+
+        // walk the frames so malformed ones do not go unnoticed.
+        let frame_data = match frames.by_ref().collect::<Result<Vec<_>, _>>() {
+            Ok(frame_data) => frame_data,
+            Err(err) => {
+                eprintln!("opusstat: s={}: {}", stream.id(), err);
+                continue;
             }
-        );
+        };
+
+        let info = frames.info();
+
+        if summary {
+            configs.entry(info).or_default().add(&Totals {
+                packets: 1,
+                frames: info.num_frames as u64,
+                bytes: data.len() as u64,
+                duration_us: info.duration_us() as u64
+            });
+        } else if dump_frames {
+            println!("s={} toc={:#04x} {}", stream.id(), info.toc, info);
+
+            for (i, frame) in frame_data.iter().enumerate() {
+                println!("  frame={} len={} dtx?={}", i, frame.len(), frame.is_empty());
+            }
+        } else if last_info != Some(info) {
+            println!("s={} toc={:#04x} {} pad={}",
+                stream.id(),
+                info.toc,
+                info,
+                padding.map_or(0, |p| p.0)
+            );
 
             num_same_conf = 0;
         } else {
@@ -55,6 +118,36 @@ fn main() {
             print!("  \r... {}", num_same_conf);
         }
 
-        last_info = Some(internal.info);
+        last_info = Some(info);
     }
-}
\ No newline at end of file
+
+    if summary {
+        let mut total = Totals::default();
+        let mut configs: Vec<_> = configs.into_iter().collect();
+
+        // in TOC order, so the table is stable across runs.
+        configs.sort_by_key(|(info, _)| (info.toc, info.is_vbr, info.num_frames));
+
+        println!("{:<40} {:>10} {:>10} {:>12} {:>12} {:>10}", "config", "packets", "frames", "bytes", "duration_ms", "kbps");
+
+        for (info, totals) in &configs {
+            print_totals(&info.to_string(), totals);
+            total.add(totals);
+        }
+
+        print_totals("total", &total);
+    }
+
+    Ok(())
+}
+
+fn print_totals(name: &str, totals: &Totals) {
+    println!("{:<40} {:>10} {:>10} {:>12} {:>12} {:>10.1}",
+        name,
+        totals.packets,
+        totals.frames,
+        totals.bytes,
+        totals.duration_us / 1000,
+        totals.bitrate_bps() as f64 / 1000.0
+    );
+}
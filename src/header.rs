@@ -0,0 +1,247 @@
+//! Identification header (`OpusHead`) of Opus streams, carried as the first
+//! packet by Ogg and signaled out of band by other transports (e.g. WebRTC).
+//!
+//! See [RFC 7845, Section 5.1][1].
+//!
+//! [1]: https://datatracker.ietf.org/doc/html/rfc7845#section-5.1
+
+use core::fmt;
+
+use alloc::vec::Vec;
+
+/// An error that occured while parsing the header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Error {
+    /// Header does not begin with the `OpusHead` magic signature.
+    BadMagic,
+    /// Header uses an incompatible (major) version.
+    UnsupportedVersion,
+    /// Header is too short to hold all of its fields.
+    TooShort,
+    /// Channel mapping table is inconsistent with the channel or stream counts.
+    BadChannelMapping,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Error::BadMagic => "header has no OpusHead magic signature",
+            Error::UnsupportedVersion => "header version is unsupported",
+            Error::TooShort => "header is too short",
+            Error::BadChannelMapping => "channel mapping table is inconsistent"
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Identification header (`OpusHead`), the first packet of an Opus stream.
+/// See [`parse_opus_head`].
+///
+/// See [RFC 7845, Section 5.1][1].
+///
+/// [1]: https://datatracker.ietf.org/doc/html/rfc7845#section-5.1
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpusHead {
+    /// Version of the encapsulation, only the major version 0 is supported.
+    pub version: u8,
+    /// Number of output channels.
+    pub channel_count: u8,
+    /// Number of samples (at 48 kHz) to discard from the decoder output
+    /// when starting playback.
+    pub pre_skip: u16,
+    /// Samplerate of the original input (before encoding), for information only.
+    pub input_sample_rate: u32,
+    /// Gain to apply to the decoder output in Q7.8 dB.
+    pub output_gain: i16,
+    /// Channel mapping family, dictating the order and meaning of channels.
+    pub mapping_family: u8,
+    /// Channel mapping table, [`None`] for family 0 (mono or stereo in a single
+    /// stream).
+    pub mapping: Option<ChannelMapping>,
+}
+
+/// Channel mapping table of a multistream, mapping output channels to the
+/// decoded channels of its Opus streams.
+///
+/// See [RFC 7845, Section 5.1.1][1].
+///
+/// [1]: https://datatracker.ietf.org/doc/html/rfc7845#section-5.1.1
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelMapping {
+    /// Number of Opus streams in each packet.
+    pub stream_count: u8,
+    /// Number of streams (the first ones) decoding to two channels.
+    pub coupled_count: u8,
+    /// Index of the decoded channel for each output channel, decoded channels
+    /// are numbered with both channels of coupled streams first. 255 silences
+    /// the output channel.
+    pub mapping: Vec<u8>,
+}
+
+impl OpusHead {
+    /// Parses an `OpusHead` packet, see [`parse_opus_head`].
+    pub fn parse(packet: &[u8]) -> Result<Self, Error> {
+        if !packet.starts_with(b"OpusHead") {
+            return Err(Error::BadMagic);
+        }
+
+        if packet.len() < 19 {
+            return Err(Error::TooShort);
+        }
+
+        let version = packet[8];
+
+        // only the minor version may change in a compatible manner.
+        if version & 0xF0 != 0 {
+            return Err(Error::UnsupportedVersion);
+        }
+
+        let channel_count = packet[9];
+        let mapping_family = packet[18];
+
+        if channel_count == 0 {
+            return Err(Error::BadChannelMapping);
+        }
+
+        let mapping = if mapping_family == 0 {
+            // a single stream, either mono or (coupled) stereo.
+            if channel_count > 2 {
+                return Err(Error::BadChannelMapping);
+            }
+
+            None
+        } else {
+            let table = packet.get(19..21 + channel_count as usize).ok_or(Error::TooShort)?;
+
+            let stream_count = table[0];
+            let coupled_count = table[1];
+            let num_decoded = stream_count as usize + coupled_count as usize;
+
+            // coupled streams are a subset of streams, whose channels must be
+            // addressable by a byte (255 is reserved for silence).
+            if stream_count == 0 || coupled_count > stream_count || num_decoded > 255 {
+                return Err(Error::BadChannelMapping);
+            }
+
+            // Vorbis channel order (family 1) is only defined upto 8 channels.
+            if mapping_family == 1 && channel_count > 8 {
+                return Err(Error::BadChannelMapping);
+            }
+
+            if table[2..].iter().any(|&index| index != 255 && index as usize >= num_decoded) {
+                return Err(Error::BadChannelMapping);
+            }
+
+            Some(ChannelMapping {
+                stream_count,
+                coupled_count,
+                mapping: table[2..].to_vec()
+            })
+        };
+
+        Ok(Self {
+            version,
+            channel_count,
+            pre_skip: u16::from_le_bytes([packet[10], packet[11]]),
+            input_sample_rate: u32::from_le_bytes([packet[12], packet[13], packet[14], packet[15]]),
+            output_gain: i16::from_le_bytes([packet[16], packet[17]]),
+            mapping_family,
+            mapping
+        })
+    }
+}
+
+impl OpusHead {
+    /// Number of samples (per channel) left for playback at `rate` out of a
+    /// decoded total of `samples` (e.g. from [`total_samples`]) once the
+    /// pre-skip is discarded.
+    /// 
+    /// [`total_samples`]: crate::packet::parser::total_samples
+    pub fn trim_pre_skip(&self, samples: u64, rate: u32) -> u64 {
+        samples.saturating_sub(self.pre_skip as u64 * rate as u64 / 48000)
+    }
+}
+
+/// Parses an `OpusHead` identification header, validating its magic signature,
+/// version and channel mapping.
+pub fn parse_opus_head(data: &[u8]) -> Result<OpusHead, Error> {
+    OpusHead::parse(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    /// `OpusHead` of family 0 with `channel_count` channels, 312 samples of
+    /// pre-skip and a 48 kHz input.
+    fn head(channel_count: u8) -> Vec<u8> {
+        let mut packet = b"OpusHead".to_vec();
+        packet.extend_from_slice(&[1, channel_count, 0x38, 0x01, 0x80, 0xbb, 0, 0, 0, 0, 0]);
+        packet
+    }
+
+    #[test]
+    fn mono_and_stereo() {
+        let mono = parse_opus_head(&head(1)).unwrap();
+
+        assert_eq!(mono, OpusHead {
+            version: 1,
+            channel_count: 1,
+            pre_skip: 312,
+            input_sample_rate: 48000,
+            output_gain: 0,
+            mapping_family: 0,
+            mapping: None
+        });
+        assert_eq!(parse_opus_head(&head(2)).unwrap().channel_count, 2);
+        assert_eq!(parse_opus_head(&head(3)), Err(Error::BadChannelMapping));
+    }
+
+    #[test]
+    fn malformed() {
+        let packet = head(2);
+
+        assert_eq!(parse_opus_head(&packet[..18]), Err(Error::TooShort));
+        assert_eq!(parse_opus_head(&packet[1..]), Err(Error::BadMagic));
+
+        let mut packet = packet;
+        packet[8] = 0x10;
+
+        assert_eq!(parse_opus_head(&packet), Err(Error::UnsupportedVersion));
+    }
+
+    #[test]
+    fn channel_mapping() {
+        // 5.1 surround out of 4 streams, 2 of them coupled.
+        let mut packet = head(6);
+        packet[18] = 1;
+        packet.extend_from_slice(&[4, 2, 0, 4, 1, 2, 3, 5]);
+
+        let mapping = parse_opus_head(&packet).unwrap().mapping.unwrap();
+
+        assert_eq!(mapping, ChannelMapping {
+            stream_count: 4,
+            coupled_count: 2,
+            mapping: vec![0, 4, 1, 2, 3, 5]
+        });
+
+        // decoded channels are numbered 0 to 5.
+        *packet.last_mut().unwrap() = 6;
+
+        assert_eq!(parse_opus_head(&packet), Err(Error::BadChannelMapping));
+        assert_eq!(parse_opus_head(&packet[..packet.len() - 1]), Err(Error::TooShort));
+    }
+
+    #[test]
+    fn pre_skip() {
+        let head = parse_opus_head(&head(1)).unwrap();
+
+        assert_eq!(head.trim_pre_skip(2280, 48000), 1968);
+        assert_eq!(head.trim_pre_skip(760, 16000), 656);
+        assert_eq!(head.trim_pre_skip(100, 48000), 0);
+    }
+}
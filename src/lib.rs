@@ -1 +1,50 @@
-pub mod packet;
\ No newline at end of file
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod packet;
+pub mod rtp;
+
+#[cfg(feature = "alloc")]
+pub mod header;
+
+#[cfg(feature = "ogg")]
+pub mod ogg;
+
+#[cfg(feature = "webm")]
+pub mod webm;
+/// Exercises the API available without `std`, so building the tests with
+/// `--no-default-features` (optionally with `alloc`) checks it stays usable.
+#[cfg(all(test, not(feature = "std")))]
+mod no_std {
+    use crate::packet::parser::{parse_into, parse_toc, FrameIter};
+
+    #[test]
+    fn parse_without_std() {
+        let packet = [0x03 | 31 << 3, 0x02, 0xff, 0xfe, 0xff, 0xfe];
+        let mut frames = [None; 48];
+
+        let (internal, num_frames) = parse_into(&mut frames, &packet).unwrap();
+
+        assert_eq!(num_frames, 2);
+        assert_eq!(internal.payload_bytes, 4);
+        assert_eq!(parse_toc(&packet).unwrap(), internal.info);
+        assert_eq!(FrameIter::new(&packet).unwrap().count(), 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn parse_with_alloc() {
+        use alloc::vec::Vec;
+
+        use crate::packet::parser::parse;
+
+        let packet = [0x01 | 31 << 3, 0xff, 0xfe, 0xff, 0xfe];
+        let mut frames = Vec::new();
+
+        parse(&mut frames, &packet).unwrap();
+
+        assert_eq!(frames, [[0xff, 0xfe], [0xff, 0xfe]]);
+    }
+}
@@ -0,0 +1,376 @@
+//! Minimal Ogg demuxer for Opus streams (`.opus` files), reassembling Opus
+//! packets out of Ogg pages so they can be handed to the
+//! [parser](crate::packet::parser) without a third-party demuxer.
+//!
+//! Only the first Opus logical stream is demuxed, pages of any other logical
+//! stream multiplexed along are skipped.
+//!
+//! See [RFC 3533][1] for the Ogg encapsulation format and [RFC 7845][2] for
+//! its mapping of Opus.
+//!
+//! [1]: https://datatracker.ietf.org/doc/html/rfc3533
+//! [2]: https://datatracker.ietf.org/doc/html/rfc7845
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Read};
+
+use crate::header;
+pub use crate::header::{ChannelMapping, OpusHead};
+
+/// Beginning of stream flag of the page header type.
+const BOS: u8 = 0x02;
+/// Continued packet flag of the page header type.
+const CONTINUED: u8 = 0x01;
+
+/// An error that occured during demuxing.
+#[derive(Debug)]
+pub enum OggError {
+    /// Reading the underlying source failed.
+    Io(io::Error),
+    /// Page does not begin with the `OggS` capture pattern.
+    NoCapture,
+    /// Page uses an unknown version of the Ogg format.
+    UnsupportedVersion,
+    /// Stream ended in the middle of a page or packet.
+    Truncated,
+    /// No Opus logical stream exists.
+    NoOpusStream,
+    /// `OpusHead` header is malformed.
+    Head(header::Error),
+    /// `OpusTags` header is malformed.
+    BadHeader,
+}
+
+impl fmt::Display for OggError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OggError::Io(err) => write!(f, "failed to read the stream: {}", err),
+            OggError::NoCapture => f.write_str("page has no capture pattern"),
+            OggError::UnsupportedVersion => f.write_str("page uses an unsupported Ogg version"),
+            OggError::Truncated => f.write_str("stream is truncated"),
+            OggError::NoOpusStream => f.write_str("stream has no Opus logical stream"),
+            OggError::Head(err) => write!(f, "OpusHead is malformed: {}", err),
+            OggError::BadHeader => f.write_str("OpusTags is malformed"),
+        }
+    }
+}
+
+impl std::error::Error for OggError {}
+
+impl From<header::Error> for OggError {
+    fn from(err: header::Error) -> Self {
+        OggError::Head(err)
+    }
+}
+
+impl From<io::Error> for OggError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => OggError::Truncated,
+            _ => OggError::Io(err)
+        }
+    }
+}
+
+/// Comment header (`OpusTags`), the second packet of an Opus stream.
+///
+/// See [RFC 7845, Section 5.2][1].
+///
+/// [1]: https://datatracker.ietf.org/doc/html/rfc7845#section-5.2
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpusTags {
+    /// Name of the encoder.
+    pub vendor: String,
+    /// User comments in `NAME=value` form.
+    pub comments: Vec<String>,
+}
+
+impl OpusTags {
+    /// Parses an `OpusTags` packet.
+    pub fn parse(packet: &[u8]) -> Result<Self, OggError> {
+        if !packet.starts_with(b"OpusTags") {
+            return Err(OggError::BadHeader);
+        }
+
+        let mut rest = &packet[8..];
+
+        let vendor = read_string(&mut rest)?;
+        let num_comments = read_u32(&mut rest)?;
+        let comments = (0..num_comments)
+            .map(|_| read_string(&mut rest))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { vendor, comments })
+    }
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<u32, OggError> {
+    let (value, rest) = bytes.split_first_chunk::<4>().ok_or(OggError::BadHeader)?;
+
+    *bytes = rest;
+
+    Ok(u32::from_le_bytes(*value))
+}
+
+fn read_string(bytes: &mut &[u8]) -> Result<String, OggError> {
+    let len = read_u32(bytes)? as usize;
+    let string = bytes.get(..len).ok_or(OggError::BadHeader)?;
+
+    *bytes = &bytes[len..];
+
+    Ok(String::from_utf8_lossy(string).into_owned())
+}
+
+/// Reader of the Opus packets of an Ogg stream.
+///
+/// The `OpusHead` and `OpusTags` headers are read upon construction, iterating
+/// yields the audio packets that follow. Iteration stops after the first error.
+pub struct OggReader<R: Read> {
+    demuxer: Demuxer<R>,
+    head: OpusHead,
+    tags: OpusTags,
+    failed: bool,
+}
+
+impl<R: Read> OggReader<R> {
+    /// Reads the headers of the first Opus logical stream of `reader`.
+    pub fn new(reader: R) -> Result<Self, OggError> {
+        let mut demuxer = Demuxer {
+            reader,
+            serial: None,
+            partial: Vec::new(),
+            packets: VecDeque::new()
+        };
+
+        let head = OpusHead::parse(&demuxer.read_packet()?.ok_or(OggError::NoOpusStream)?)?;
+        let tags = OpusTags::parse(&demuxer.read_packet()?.ok_or(OggError::Truncated)?)?;
+
+        Ok(Self { demuxer, head, tags, failed: false })
+    }
+
+    /// Identification header of the stream.
+    pub fn head(&self) -> &OpusHead {
+        &self.head
+    }
+
+    /// Comment header of the stream.
+    pub fn tags(&self) -> &OpusTags {
+        &self.tags
+    }
+}
+
+impl<R: Read> Iterator for OggReader<R> {
+    type Item = Result<Vec<u8>, OggError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let packet = self.demuxer.read_packet().transpose();
+
+        self.failed = matches!(packet, Some(Err(_)));
+
+        packet
+    }
+}
+
+/// Reassembles the packets of a single logical stream.
+struct Demuxer<R: Read> {
+    reader: R,
+    /// Serial number of the demuxed logical stream.
+    serial: Option<u32>,
+    /// Packet continued across pages.
+    partial: Vec<u8>,
+    /// Packets completed in the last read page.
+    packets: VecDeque<Vec<u8>>,
+}
+
+impl<R: Read> Demuxer<R> {
+    /// Reads the next packet of the logical stream, [`None`] at the end of the stream.
+    fn read_packet(&mut self) -> Result<Option<Vec<u8>>, OggError> {
+        loop {
+            if let Some(packet) = self.packets.pop_front() {
+                return Ok(Some(packet));
+            }
+
+            if !self.read_page()? {
+                return if self.partial.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(OggError::Truncated)
+                };
+            }
+        }
+    }
+
+    /// Reads the next page, splitting it into packets if it belongs to the logical
+    /// stream. Returns `false` at the end of the stream.
+    fn read_page(&mut self) -> Result<bool, OggError> {
+        //  0                   1                   2                   3
+        //  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+        // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+        // | capture_pattern: Magic number for page start "OggS"           |
+        // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+        // | version       | header_type   | granule_position              |
+        // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+        // |                                                               |
+        // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+        // |                               | bitstream_serial_number       |
+        // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+        // |                               | page_sequence_number          |
+        // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+        // |                               | CRC_checksum                  |
+        // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+        // |                               |page_segments  | segment_table |
+        // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+        let mut header = [0; 27];
+
+        // a stream may only end between pages.
+        loop {
+            match self.reader.read(&mut header[..1]) {
+                Ok(0) => return Ok(false),
+                Ok(_) => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into())
+            }
+        }
+
+        self.reader.read_exact(&mut header[1..])?;
+
+        if &header[..4] != b"OggS" {
+            return Err(OggError::NoCapture);
+        }
+
+        if header[4] != 0 {
+            return Err(OggError::UnsupportedVersion);
+        }
+
+        let header_type = header[5];
+        let serial = u32::from_le_bytes([header[14], header[15], header[16], header[17]]);
+
+        let mut lacing = vec![0; header[26] as usize];
+        self.reader.read_exact(&mut lacing)?;
+
+        let mut data = vec![0; lacing.iter().map(|&len| len as usize).sum()];
+        self.reader.read_exact(&mut data)?;
+
+        // the first Opus stream begins with the identification header.
+        if self.serial.is_none() && header_type & BOS != 0 && data.starts_with(b"OpusHead") {
+            self.serial = Some(serial);
+        }
+
+        if self.serial != Some(serial) {
+            return Ok(true);
+        }
+
+        // packet continued from a lost page cannot be completed.
+        if header_type & CONTINUED == 0 {
+            self.partial.clear();
+        }
+
+        // nor can one continued onto this page if its beginning was lost, e.g.
+        // after seeking.
+        let mut orphaned = header_type & CONTINUED != 0 && self.partial.is_empty();
+        let mut data = &data[..];
+
+        for len in lacing {
+            let (segment, rest) = data.split_at(len as usize);
+
+            data = rest;
+
+            if orphaned {
+                orphaned = len == 255;
+                continue;
+            }
+
+            self.partial.extend_from_slice(segment);
+
+            // a lacing value less than 255 terminates the packet.
+            if len < 255 {
+                self.packets.push_back(std::mem::take(&mut self.partial));
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::parser::{parse, Mode};
+
+    const SAMPLE: &[u8] = include_bytes!("../tests/fixtures/sample.opus");
+
+    #[test]
+    fn demux_fixture() {
+        let reader = OggReader::new(SAMPLE).unwrap();
+
+        assert_eq!(reader.head().channel_count, 1);
+        assert_eq!(reader.head().pre_skip, 312);
+        assert_eq!(reader.tags().vendor, "opus-rs!");
+        assert_eq!(reader.tags().comments, ["TITLE=test"]);
+
+        let packets = reader.collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(packets.len(), 11);
+        assert_eq!(packets[0], [0xf8, 0xff, 0xfe]);
+        // spans the last two pages.
+        assert_eq!(packets[10].len(), 607);
+
+        for packet in &packets {
+            let info = parse(&mut Vec::new(), packet).unwrap().info;
+
+            assert_eq!(info.mode(), Mode::CELT);
+        }
+    }
+
+    #[test]
+    fn truncated_fixture() {
+        let reader = OggReader::new(&SAMPLE[..SAMPLE.len() - 100]).unwrap();
+        let last = reader.last().unwrap();
+
+        assert!(matches!(last, Err(OggError::Truncated)));
+    }
+
+
+    /// Reader interrupted before every read.
+    struct Interrupting<'a> {
+        data: &'a [u8],
+        interrupted: bool
+    }
+
+    impl Read for Interrupting<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.interrupted = !self.interrupted;
+
+            if self.interrupted {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+
+            self.data.read(buf)
+        }
+    }
+
+    #[test]
+    fn interrupted_reads() {
+        let reader = OggReader::new(Interrupting { data: SAMPLE, interrupted: false }).unwrap();
+        let packets = reader.collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(packets.len(), 11);
+    }
+
+    #[test]
+    fn orphaned_continuation() {
+        // without the third page, the last page begins with the end of a packet.
+        let mut sample = SAMPLE[..113].to_vec();
+        sample.extend_from_slice(&SAMPLE[709..]);
+
+        let reader = OggReader::new(&sample[..]).unwrap();
+        let packets = reader.collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert!(packets.is_empty());
+    }
+}
@@ -0,0 +1,579 @@
+//! This module assembles Opus frames back into packets, the inverse of the
+//! [parser](super::parser).
+//! 
+//! See [RFC 6716, Section 3.2][1].
+//!
+//! [1]: https://datatracker.ietf.org/doc/html/rfc6716#section-3.2
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+use arrayvec::ArrayVec;
+
+use super::config::*;
+use super::parser::{Code, Error};
+#[cfg(feature = "alloc")]
+use super::parser::{parse, FrameIter, Info};
+
+/// Builder of non-self-delimiting Opus packets out of isoconfig frames.
+/// 
+/// Unless forced with [`PacketBuilder::code`], the smallest code able to carry
+/// the frames is chosen, i.e. Code 0 for one frame, Code 1 for two equal frames,
+/// Code 2 for two unequal frames and Code 3 otherwise (or if padded).
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct PacketBuilder<'a> {
+    frame_config: FrameConfig,
+    frames: Vec<&'a [u8]>,
+    padding: usize,
+    code: Option<Code>,
+    is_vbr: Option<bool>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> PacketBuilder<'a> {
+    /// Builder of a packet without any frames yet.
+    pub fn new(frame_config: FrameConfig) -> Self {
+        Self {
+            frame_config,
+            frames: Vec::new(),
+            padding: 0,
+            code: None,
+            is_vbr: None
+        }
+    }
+
+    /// Appends a frame.
+    pub fn frame(mut self, frame: &'a [u8]) -> Self {
+        self.frames.push(frame);
+        self
+    }
+
+    /// Appends several frames.
+    pub fn frames(mut self, frames: &[&'a [u8]]) -> Self {
+        self.frames.extend_from_slice(frames);
+        self
+    }
+
+    /// Total number of bytes added to the packet for padding (as in
+    /// [`Internal::padding`](super::parser::Internal::padding)), zero for none.
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Forces the code of the packet rather than choosing the smallest one.
+    pub fn code(mut self, code: Code) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Forces VBR (length prefixed frames) or CBR (equal length frames) for
+    /// Code 3 packets, rather than using CBR only if frames are of equal length.
+    /// 
+    /// Has no effect on the other codes.
+    pub fn vbr(mut self, is_vbr: bool) -> Self {
+        self.is_vbr = Some(is_vbr);
+        self
+    }
+
+    /// Writes the packet.
+    pub fn build(&self) -> Result<Vec<u8>, Error> {
+        let frames = &self.frames[..];
+        let padding = self.padding;
+
+        if frames.is_empty() {
+            return Err(Error::NoAudio);
+        }
+
+        if frames.iter().any(|frame| frame.len() > 1275) {
+            return Err(Error::FrameTooBig);
+        }
+
+        if frames.len() > max_frames(self.frame_config.config.framesize) {
+            return Err(Error::TooMuchAudio);
+        }
+
+        let is_uniform = frames.iter().all(|frame| frame.len() == frames[0].len());
+        let is_cbr = self.is_vbr.map_or(is_uniform, |is_vbr| !is_vbr);
+        let code = match (self.code, frames.len()) {
+            (Some(code), _) => code,
+            _ if padding > 0 => Code::Code3,
+            (None, 1) => Code::Code0,
+            (None, 2) if is_uniform => Code::Code1,
+            (None, 2) => Code::Code2,
+            (None, _) => Code::Code3
+        };
+
+        let fits = match code {
+            Code::Code0 => frames.len() == 1,
+            Code::Code1 => frames.len() == 2 && is_uniform,
+            Code::Code2 => frames.len() == 2,
+            Code::Code3 => true
+        };
+
+        // only Code 3 packets can be padded.
+        if !fits || (padding > 0 && code != Code::Code3) {
+            return Err(Error::CodeMismatch);
+        }
+
+        if code == Code::Code3 && is_cbr && !is_uniform {
+            return Err(Error::NonUniformFrames);
+        }
+
+        let toc = encode_toc(&self.frame_config, code).ok_or(Error::InvalidConfig)?;
+        let mut packet = vec![toc];
+
+        match code {
+            Code::Code0 | Code::Code1 => {},
+
+            Code::Code2 => packet.extend(encode_frame_length(frames[0].len())?),
+
+            Code::Code3 => {
+                //  0 1 2 3 4 5 6 7
+                // +-+-+-+-+-+-+-+-+
+                // |v|p|     M     |
+                // +-+-+-+-+-+-+-+-+
+                packet.push((!is_cbr as u8) << 7 | ((padding > 0) as u8) << 6 | frames.len() as u8);
+
+                if padding > 0 {
+                    // each 255 stands for 254 bytes of padding plus the next byte,
+                    // making P = 255 for every one of them.
+                    let n_chained = (padding - 1) / 255;
+
+                    packet.extend(core::iter::repeat_n(255, n_chained));
+                    packet.push(((padding - 1) % 255) as u8);
+                }
+
+                if !is_cbr {
+                    for frame in &frames[..frames.len() - 1] {
+                        packet.extend(encode_frame_length(frame.len())?);
+                    }
+                }
+            }
+        }
+
+        for frame in frames {
+            packet.extend_from_slice(frame);
+        }
+
+        if padding > 0 {
+            let pad_len = padding - (padding - 1) / 255 - 1;
+
+            packet.resize(packet.len() + pad_len, 0);
+        }
+
+        Ok(packet)
+    }
+}
+
+/// Merges the frames of several packets sharing the same [`FrameConfig`] into a
+/// single Code 3 packet of upto 120 ms, akin to `opus_repacketizer` of libopus.
+/// 
+/// Frames are borrowed from the concatenated packets until written out.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct Repacketizer<'a> {
+    frame_config: Option<FrameConfig>,
+    frames: Vec<&'a [u8]>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Repacketizer<'a> {
+    /// Repacketizer without any frames yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the frames of a non-self-delimiting packet. Nothing is added if the
+    /// packet is malformed, its configuration differs from the packets added so
+    /// far or the merged packet would exceed 120 ms.
+    pub fn cat(&mut self, packet: &'a [u8]) -> Result<(), Error> {
+        let frames = FrameIter::new(packet)?;
+        let info = frames.info();
+
+        // only isoconfig frames may share a packet.
+        if self.frame_config.is_some_and(|frame_config| frame_config != info.frame_config) {
+            return Err(Error::ConfigMismatch);
+        }
+
+        let num_frames = self.frames.len() + info.num_frames;
+
+        if num_frames > max_frames(info.frame_config.config.framesize) {
+            return Err(Error::TooMuchAudio);
+        }
+
+        let num_merged = self.frames.len();
+
+        for frame in frames {
+            match frame {
+                Ok(frame) => self.frames.push(frame),
+                Err(err) => {
+                    self.frames.truncate(num_merged);
+                    return Err(err);
+                }
+            }
+        }
+
+        self.frame_config = Some(info.frame_config);
+
+        Ok(())
+    }
+
+    /// Number of frames added so far.
+    pub fn num_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Writes the merged Code 3 packet.
+    pub fn out(&self) -> Result<Vec<u8>, Error> {
+        let frame_config = self.frame_config.ok_or(Error::NoAudio)?;
+
+        PacketBuilder::new(frame_config)
+            .frames(&self.frames)
+            .code(Code::Code3)
+            .build()
+    }
+
+    /// Drops all the frames added so far, so it can be reused for other packets.
+    pub fn reset(&mut self) {
+        self.frame_config = None;
+        self.frames.clear();
+    }
+}
+
+/// Splits a non-self-delimiting packet into single-frame (Code 0) packets of the
+/// same configuration pushed to `out`, dropping any padding. Nothing is pushed
+/// if the packet is malformed.
+#[cfg(feature = "alloc")]
+pub fn split_packet(packet: &[u8], out: &mut Vec<Vec<u8>>) -> Result<Info, Error> {
+    let mut frames = FrameIter::new(packet)?;
+    let toc = encode_toc(&frames.info().frame_config, Code::Code0).ok_or(Error::InvalidConfig)?;
+    let num_split = out.len();
+
+    for frame in frames.by_ref() {
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(err) => {
+                out.truncate(num_split);
+                return Err(err);
+            }
+        };
+
+        let mut single = Vec::with_capacity(1 + frame.len());
+
+        single.push(toc);
+        single.extend_from_slice(frame);
+        out.push(single);
+    }
+
+    Ok(frames.info())
+}
+
+/// Splits a non-self-delimiting packet into single-frame (Code 0) packets, see
+/// [`split_packet`].
+#[cfg(feature = "alloc")]
+pub fn split(packet: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+    let mut out = Vec::new();
+
+    split_packet(packet, &mut out)?;
+
+    Ok(out)
+}
+
+/// Drops the padding of a non-self-delimiting packet, clearing the padding flag
+/// and removing the padding length bytes along with the padding itself. Packets
+/// without padding (including all Code 0-2 packets) are copied unchanged.
+#[cfg(feature = "alloc")]
+pub fn strip_padding(packet: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut frames = Vec::new();
+    let internal = parse(&mut frames, packet)?;
+
+    if internal.padding.is_none() {
+        return Ok(packet.to_vec());
+    }
+
+    PacketBuilder::new(internal.info.frame_config)
+        .frames(&frames)
+        .code(Code::Code3)
+        .vbr(internal.info.is_vbr == Some(true))
+        .build()
+}
+
+/// Writes a non-self-delimiting Opus packet out of isoconfig `frames` with the
+/// smallest code able to carry them, see [`PacketBuilder`].
+/// 
+/// `padding` is the total number of bytes added to the packet for padding (as in
+/// [`Internal::padding`](super::parser::Internal::padding)), which is only
+/// possible with Code 3.
+#[cfg(feature = "alloc")]
+pub fn write_packet(
+    frame_config: &FrameConfig,
+    frames: &[&[u8]],
+    padding: Option<usize>) -> Result<Vec<u8>, Error>
+{
+    PacketBuilder::new(*frame_config)
+        .frames(frames)
+        .padding(padding.unwrap_or(0))
+        .build()
+}
+
+/// Builds a TOC byte out of its fields, returning [`None`] if the configuration
+/// is not one of [`OPUS_CONFIG_TABLE`]. See [`encode_toc`].
+pub fn build_toc(config: &Config, is_stereo: bool, code: Code) -> Option<u8> {
+    encode_toc(&FrameConfig { config: *config, is_stereo }, code)
+}
+
+/// Encodes a frame length field, the inverse of its parsing. Lengths upto 251
+/// take a single byte, larger ones (upto 1275) take two.
+/// 
+/// See [RFC 6716, Section 3.2.1][1].
+/// 
+/// [1]: https://datatracker.ietf.org/doc/html/rfc6716#section-3.2.1
+pub fn encode_frame_length(length: usize) -> Result<ArrayVec<u8, 2>, Error> {
+    let mut bytes = ArrayVec::new();
+
+    if length > 1275 {
+        return Err(Error::FrameTooBig);
+    }
+
+    if length < 252 {
+        bytes.push(length as u8);
+    } else {
+        // length = first + second * 4, where first is 252...255.
+        let first = 252 + (length & 0x3);
+
+        bytes.push(first as u8);
+        bytes.push(((length - first) / 4) as u8);
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    /// CELT-only full-band 2.5 ms, stereo.
+    const TOC_CELT_2_5MS: u8 = 28 << 3 | 0x4;
+
+    /// Parses `packet` back into its info and frames.
+    fn reparse(packet: &[u8]) -> (Info, Vec<&[u8]>) {
+        let mut frames = Vec::new();
+        let info = parse(&mut frames, packet).unwrap().info;
+
+        (info, frames)
+    }
+
+    #[test]
+    fn write_packet_round_trip() {
+        let frame_config = FrameConfig::from_toc(TOC_CELT_2_5MS);
+        // frames, padding and the code written.
+        type Case<'a> = (&'a [&'a [u8]], Option<usize>, Code);
+
+        let cases: [Case; 5] = [
+            (&[&[1, 2, 3]], None, Code::Code0),
+            (&[&[1, 2], &[3, 4]], None, Code::Code1),
+            (&[&[1], &[2, 3]], None, Code::Code2),
+            (&[&[1], &[2], &[3, 4]], None, Code::Code3),
+            (&[&[1, 2]], Some(10), Code::Code3),
+        ];
+
+        for (frames, padding, code) in cases {
+            let packet = write_packet(&frame_config, frames, padding).unwrap();
+            let (info, parsed) = reparse(&packet);
+
+            assert_eq!(info.code_no, code);
+            assert_eq!(info.frame_config, frame_config);
+            assert_eq!(parsed, frames);
+        }
+    }
+
+    #[test]
+    fn build_toc_round_trip() {
+        for (index, config) in OPUS_CONFIG_TABLE.iter().enumerate() {
+            for is_stereo in [false, true] {
+                for code in [Code::Code0, Code::Code1, Code::Code2, Code::Code3] {
+                    let toc = build_toc(config, is_stereo, code).unwrap();
+
+                    assert_eq!(toc >> 3, index as u8);
+                    assert_eq!(FrameConfig::from_toc(toc), FrameConfig { config: *config, is_stereo });
+                    assert_eq!(Code::from_toc(toc), code);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn builder_round_trip() {
+        let frame_config = FrameConfig::from_toc(TOC_CELT_2_5MS);
+        let frame = [0x55; 300];
+
+        let packet = PacketBuilder::new(frame_config)
+            .frame(&frame)
+            .frame(&frame[..10])
+            .build()
+            .unwrap();
+        let (info, frames) = reparse(&packet);
+
+        assert_eq!(info.code_no, Code::Code2);
+        assert_eq!(frames, [&frame[..], &frame[..10]]);
+
+        // two equal frames in a Code 3 packet, rather than the smallest Code 1.
+        let packet = PacketBuilder::new(frame_config)
+            .frames(&[&frame[..4], &frame[..4]])
+            .code(Code::Code3)
+            .build()
+            .unwrap();
+        let (info, frames) = reparse(&packet);
+
+        assert_eq!(info.code_no, Code::Code3);
+        assert_eq!(info.num_frames, 2);
+        assert_eq!(frames, [&frame[..4], &frame[..4]]);
+
+        let builder = PacketBuilder::new(frame_config).frames(&[&frame[..1], &frame[..2]]);
+
+        assert_eq!(builder.clone().code(Code::Code1).build(), Err(Error::CodeMismatch));
+        assert_eq!(builder.code(Code::Code0).build(), Err(Error::CodeMismatch));
+        assert_eq!(PacketBuilder::new(frame_config).build(), Err(Error::NoAudio));
+    }
+
+    #[test]
+    fn frame_length_round_trip() {
+        use super::super::utils::parse_frame_length;
+
+        for length in 0..=1275 {
+            let bytes = encode_frame_length(length).unwrap();
+
+            assert_eq!(parse_frame_length(&bytes), Some((length, bytes.len())));
+        }
+
+        assert_eq!(encode_frame_length(1276), Err(Error::FrameTooBig));
+    }
+
+    #[test]
+    fn builder_vbr_bit() {
+        let frame_config = FrameConfig::from_toc(TOC_CELT_2_5MS);
+        let equal: [&[u8]; 3] = [&[1, 2], &[3, 4], &[5, 6]];
+
+        for is_vbr in [false, true] {
+            let packet = PacketBuilder::new(frame_config)
+                .frames(&equal)
+                .vbr(is_vbr)
+                .build()
+                .unwrap();
+            let (info, frames) = reparse(&packet);
+
+            assert_eq!(packet[1] >> 7, is_vbr as u8);
+            assert_eq!(info.is_vbr, Some(is_vbr));
+            assert_eq!(frames, equal);
+        }
+
+        let unequal = PacketBuilder::new(frame_config)
+            .frames(&[&[1], &[2, 3], &[4]])
+            .vbr(false)
+            .build();
+
+        assert_eq!(unequal, Err(Error::NonUniformFrames));
+    }
+
+    #[test]
+    fn repacketize() {
+        // CELT-only full-band 20 ms, mono.
+        let packets = [[31 << 3, 1, 2], [31 << 3, 3, 4], [31 << 3, 5, 6]];
+        let mut rp = Repacketizer::new();
+
+        for packet in &packets {
+            rp.cat(packet).unwrap();
+        }
+
+        let packet = rp.out().unwrap();
+        let (info, frames) = reparse(&packet);
+
+        assert_eq!(info.code_no, Code::Code3);
+        assert_eq!(info.duration_ms(), 60.0);
+        assert_eq!(frames, [[1, 2], [3, 4], [5, 6]]);
+
+        // stereo, and 20 ms past 120 ms.
+        assert_eq!(rp.cat(&[31 << 3 | 0x4, 1]), Err(Error::ConfigMismatch));
+        assert_eq!(rp.cat(&[31 << 3 | 0x3, 0x04, 1, 2, 3, 4]), Err(Error::TooMuchAudio));
+        assert_eq!(rp.num_frames(), 3);
+    }
+
+    #[test]
+    fn split_vbr_packet() {
+        // padded VBR of 1, 3 and 2 bytes.
+        let packet = [TOC_CELT_2_5MS | 0x3, 0xc3, 1, 1, 3, 1, 2, 3, 4, 5, 6, 0];
+        let mut out = Vec::new();
+        let info = split_packet(&packet, &mut out).unwrap();
+
+        assert_eq!(info.num_frames, 3);
+        assert_eq!(out, [&[TOC_CELT_2_5MS, 1][..], &[TOC_CELT_2_5MS, 2, 3, 4][..], &[TOC_CELT_2_5MS, 5, 6][..]]);
+
+        for single in &out {
+            let (single_info, _) = reparse(single);
+
+            assert_eq!(single_info.code_no, Code::Code0);
+            assert_eq!(single_info.frame_config, info.frame_config);
+        }
+    }
+
+    #[test]
+    fn split_and_repacketize() {
+        let packet = [TOC_CELT_2_5MS | 0x3, 0x83, 1, 3, 1, 2, 3, 4, 5, 6];
+        let singles = split(&packet).unwrap();
+
+        assert!(singles.iter().all(|single| single[0] == TOC_CELT_2_5MS));
+
+        let mut rp = Repacketizer::new();
+
+        for single in &singles {
+            rp.cat(single).unwrap();
+        }
+
+        assert_eq!(rp.out().unwrap(), packet);
+    }
+
+    #[test]
+    fn strip_padding_round_trip() {
+        let frame_config = FrameConfig::from_toc(TOC_CELT_2_5MS);
+        let frames: [&[u8]; 3] = [&[1], &[2, 3], &[4]];
+
+        for padding in [1, 2, 300] {
+            let padded = write_packet(&frame_config, &frames, Some(padding)).unwrap();
+            let stripped = strip_padding(&padded).unwrap();
+            let internal = parse(&mut Vec::new(), &stripped).unwrap();
+
+            assert_eq!(stripped.len(), padded.len() - padding);
+            assert_eq!(internal.padding, None);
+            assert_eq!(reparse(&stripped).1, frames);
+        }
+
+        let unpadded = [TOC_CELT_2_5MS | 0x2, 1, 1, 2];
+
+        assert_eq!(strip_padding(&unpadded).unwrap(), unpadded);
+    }
+
+    #[test]
+    fn builder_padding() {
+        let frame_config = FrameConfig::from_toc(TOC_CELT_2_5MS);
+
+        // total padding along its length bytes, i.e. 253; 254; 255, 0 and
+        // 255, 255, 255, 234 (each 255 adding 254 bytes).
+        for (padding, length_bytes) in [(254, &[253][..]), (255, &[254]), (256, &[255, 0]), (1000, &[255, 255, 255, 234])] {
+            let packet = PacketBuilder::new(frame_config)
+                .frame(&[1, 2])
+                .padding(padding)
+                .build()
+                .unwrap();
+            let mut frames = Vec::new();
+            let internal = parse(&mut frames, &packet).unwrap();
+
+            assert_eq!(packet[1] & 0x40, 0x40);
+            assert_eq!(&packet[2..2 + length_bytes.len()], length_bytes);
+            assert_eq!(internal.padding.unwrap().0, padding);
+            assert_eq!(packet.len(), 2 + 2 + padding);
+            assert_eq!(frames, [[1, 2]]);
+        }
+    }
+}
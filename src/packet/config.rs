@@ -7,7 +7,10 @@
 //!
 //! [1]: (https://datatracker.ietf.org/doc/html/rfc6716#section-3.1)
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+use super::parser::{Code, Error};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Operating mode used for packet coding.
 pub enum Mode {
     /// [SILK][2]-only mode for use in low bitrate with wide-band or
@@ -25,7 +28,8 @@ pub enum Mode {
     Hybrid
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Bandwidth of the transmitted signal.
 pub enum Bandwidth {
     /// 0-4 kHz (8 kHz samplerate).
@@ -46,21 +50,118 @@ pub enum Bandwidth {
     FullBand
 }
 
+impl Bandwidth {
+    /// Nominal samplerate of the coded bandwidth, i.e. twice its upper limit
+    /// (except for full-band).
+    /// 
+    /// Note that it is not the rate an Opus decoder outputs at, which is chosen
+    /// freely regardless of the coded bandwidth.
+    pub const fn sample_rate(self) -> u32 {
+        match self {
+            Bandwidth::Narrow => 8000,
+            Bandwidth::Medium => 12000,
+            Bandwidth::Wide => 16000,
+            Bandwidth::SuperWide => 24000,
+            Bandwidth::FullBand => 48000
+        }
+    }
+
+    /// Upper limit of the coded audio in Hz.
+    pub const fn max_audio_hz(self) -> u32 {
+        match self {
+            Bandwidth::Narrow => 4000,
+            Bandwidth::Medium => 6000,
+            Bandwidth::Wide => 8000,
+            Bandwidth::SuperWide => 12000,
+            Bandwidth::FullBand => 20000
+        }
+    }
+}
+
 /// TOC configuration field.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config {
     pub mode: Mode,
     pub bandwith: Bandwidth,
-    /// Length of an Opus frame, can be 2.5/5/10/20/40/60 ms depending
-    /// on the mode used for coding. Any other value pertains to [Opus
-    /// custom][5], which is unsupported here.
-    /// 
-    /// [5]: https://datatracker.ietf.org/doc/html/rfc6716#section-6.2
-    pub framesize: f32,
+    /// Length of an Opus frame, see [`Config::framesize`] for it in milliseconds.
+    pub framesize: FrameSize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "f32", try_from = "f32"))]
+/// Length of an Opus frame, can be 2.5/5/10/20/40/60 ms depending on the mode
+/// used for coding. Any other length pertains to [Opus custom][5], which is
+/// unsupported here.
+/// 
+/// Serialized as the length in milliseconds.
+/// 
+/// [5]: https://datatracker.ietf.org/doc/html/rfc6716#section-6.2
+pub enum FrameSize {
+    /// 2.5 ms (CELT-only).
+    Ms2_5,
+    /// 5 ms (CELT-only).
+    Ms5,
+    /// 10 ms.
+    Ms10,
+    /// 20 ms.
+    Ms20,
+    /// 40 ms (SILK-only).
+    Ms40,
+    /// 60 ms (SILK-only).
+    Ms60
 }
 
+impl FrameSize {
+    /// Length in microseconds, exact unlike the length in milliseconds
+    /// and thus preferable for arithmetic.
+    pub const fn as_micros(self) -> u32 {
+        match self {
+            FrameSize::Ms2_5 => 2500,
+            FrameSize::Ms5 => 5000,
+            FrameSize::Ms10 => 10000,
+            FrameSize::Ms20 => 20000,
+            FrameSize::Ms40 => 40000,
+            FrameSize::Ms60 => 60000
+        }
+    }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+    /// Length in milliseconds.
+    pub fn as_millis(self) -> f32 {
+        self.as_micros() as f32 / 1000.0
+    }
+}
+
+impl From<FrameSize> for f32 {
+    fn from(framesize: FrameSize) -> Self {
+        framesize.as_millis()
+    }
+}
+
+impl TryFrom<f32> for FrameSize {
+    type Error = Error;
+
+    /// Converts a length in milliseconds, failing with [`Error::InvalidConfig`]
+    /// if it is not one of the Opus frame sizes.
+    fn try_from(millis: f32) -> Result<Self, Self::Error> {
+        [
+            FrameSize::Ms2_5,
+            FrameSize::Ms5,
+            FrameSize::Ms10,
+            FrameSize::Ms20,
+            FrameSize::Ms40,
+            FrameSize::Ms60
+        ]
+        .into_iter()
+        .find(|framesize| framesize.as_millis() == millis)
+        .ok_or(Error::InvalidConfig)
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Coding configuration of a Opus frame, it is one of the major dictating
 /// factors of grouping multiple frames in a packet.
 /// 
@@ -77,6 +178,113 @@ pub struct FrameConfig {
     pub is_stereo: bool 
 }
 
+impl Config {
+    /// Configuration of `mode`, `bandwidth` and `framesize` in milliseconds, or
+    /// [`None`] if it is not one of [`OPUS_CONFIG_TABLE`] (e.g. SILK-only at
+    /// 2.5 ms or Hybrid narrow-band).
+    pub fn new(mode: Mode, bandwidth: Bandwidth, framesize: f32) -> Option<Config> {
+        OPUS_CONFIG_TABLE
+            .iter()
+            .find(|config| {
+                config.mode == mode
+                    && config.bandwith == bandwidth
+                    && config.framesize() == framesize
+            })
+            .copied()
+    }
+
+    /// Whether the mode is [`Mode::CELT`].
+    pub fn is_celt_only(&self) -> bool {
+        self.mode == Mode::CELT
+    }
+
+    /// Whether the mode is [`Mode::SILK`].
+    pub fn is_silk_only(&self) -> bool {
+        self.mode == Mode::SILK
+    }
+
+    /// Whether the mode is [`Mode::Hybrid`].
+    pub fn is_hybrid(&self) -> bool {
+        self.mode == Mode::Hybrid
+    }
+
+    /// Index of the configuration in [`OPUS_CONFIG_TABLE`], i.e. the `config`
+    /// field of the TOC byte, or [`None`] if it is not representable.
+    pub fn to_toc_index(&self) -> Option<u8> {
+        OPUS_CONFIG_TABLE
+            .iter()
+            .position(|config| config == self)
+            .map(|index| index as u8)
+    }
+
+    /// Length of an Opus frame in milliseconds.
+    pub fn framesize(&self) -> f32 {
+        self.framesize.as_millis()
+    }
+
+    /// Number of PCM samples (per channel) a frame decodes to at `rate`, see
+    /// [`Config::samples_per_frame`].
+    pub const fn frame_samples(&self, rate: u32) -> usize {
+        self.samples_per_frame(rate)
+    }
+
+    /// Number of PCM samples (per channel) a frame decodes to at `output_rate`,
+    /// e.g. for sizing playback buffers. A 2.5 ms frame is 120 samples and a 60 ms
+    /// frame 2880 samples at 48 kHz.
+    /// 
+    /// Opus decodes to any of 8, 12, 16, 24 or 48 kHz (all dividing 48 kHz)
+    /// regardless of the coded bandwidth, which only limits the usable spectrum
+    /// of the output, see [`Bandwidth::max_audio_hz`].
+    pub const fn samples_per_frame(&self, output_rate: u32) -> usize {
+        (self.framesize.as_micros() as u64 * output_rate as u64 / 1_000_000) as usize
+    }
+
+    /// Largest number of PCM samples (per channel) any frame of
+    /// [`OPUS_CONFIG_TABLE`] decodes to, i.e. 2880 samples of a 60 ms frame at
+    /// 48 kHz, for allocating decode buffers once.
+    pub const fn max_frame_samples() -> usize {
+        let mut max = 0;
+        let mut index = 0;
+
+        while index < OPUS_CONFIG_TABLE.len() {
+            let samples = OPUS_CONFIG_TABLE[index].samples_per_frame(48000);
+
+            if samples > max {
+                max = samples;
+            }
+
+            index += 1;
+        }
+
+        max
+    }
+
+    /// Maximum number of frames of this configuration a packet can carry, see
+    /// [`max_frames`].
+    pub const fn max_frames(&self) -> usize {
+        max_frames(self.framesize)
+    }
+}
+
+impl FrameConfig {
+    /// Frame configuration of a TOC byte.
+    pub fn from_toc(toc: u8) -> Self {
+        //  0 1 2 3 4 5 6 7
+        // +-+-+-+-+-+-+-+-+
+        // | config  |s| c |
+        // +-+-+-+-+-+-+-+-+
+        Self {
+            config: OPUS_CONFIG_TABLE[(toc >> 3) as usize],
+            is_stereo: toc & 0x4 != 0
+        }
+    }
+
+    /// Number of channels, 2 if stereophonic otherwise 1.
+    pub fn channels(&self) -> u8 {
+        if self.is_stereo { 2 } else { 1 }
+    }
+}
+
 impl Default for Config {
     /// Default according to the reference implementation (libopus).
     /// 
@@ -86,7 +294,7 @@ impl Default for Config {
         Self { 
             mode: Mode::CELT, 
             bandwith: Bandwidth::FullBand, 
-            framesize: 20.0
+            framesize: FrameSize::Ms20
         }
     }
 }
@@ -103,45 +311,249 @@ impl Default for FrameConfig {
 }
 
 /// Possible configurations according to the `config` field of the TOC byte.
+/// 
+/// Prefer [`config_for_index`] for a bounds-checked lookup.
 pub static OPUS_CONFIG_TABLE: [Config; 32] = [
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Narrow, framesize: 10.0},
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Narrow, framesize: 20.0},
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Narrow, framesize: 40.0},
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Narrow, framesize: 60.0},
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Narrow, framesize: FrameSize::Ms10},
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Narrow, framesize: FrameSize::Ms20},
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Narrow, framesize: FrameSize::Ms40},
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Narrow, framesize: FrameSize::Ms60},
     
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Medium, framesize: 10.0},
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Medium, framesize: 20.0},
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Medium, framesize: 40.0},
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Medium, framesize: 60.0},
-
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Wide, framesize: 10.0},
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Wide, framesize: 20.0},
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Wide, framesize: 40.0},
-    Config {mode: Mode::SILK, bandwith: Bandwidth::Wide, framesize: 60.0},
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Medium, framesize: FrameSize::Ms10},
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Medium, framesize: FrameSize::Ms20},
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Medium, framesize: FrameSize::Ms40},
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Medium, framesize: FrameSize::Ms60},
+
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Wide, framesize: FrameSize::Ms10},
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Wide, framesize: FrameSize::Ms20},
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Wide, framesize: FrameSize::Ms40},
+    Config {mode: Mode::SILK, bandwith: Bandwidth::Wide, framesize: FrameSize::Ms60},
     
-    Config {mode: Mode::Hybrid, bandwith: Bandwidth::SuperWide, framesize: 10.0},
-    Config {mode: Mode::Hybrid, bandwith: Bandwidth::SuperWide, framesize: 20.0},
+    Config {mode: Mode::Hybrid, bandwith: Bandwidth::SuperWide, framesize: FrameSize::Ms10},
+    Config {mode: Mode::Hybrid, bandwith: Bandwidth::SuperWide, framesize: FrameSize::Ms20},
     
-    Config {mode: Mode::Hybrid, bandwith: Bandwidth::FullBand, framesize: 10.0},
-    Config {mode: Mode::Hybrid, bandwith: Bandwidth::FullBand, framesize: 20.0},
+    Config {mode: Mode::Hybrid, bandwith: Bandwidth::FullBand, framesize: FrameSize::Ms10},
+    Config {mode: Mode::Hybrid, bandwith: Bandwidth::FullBand, framesize: FrameSize::Ms20},
     
-    Config {mode: Mode::CELT, bandwith: Bandwidth::Narrow, framesize: 2.5},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::Narrow, framesize: 5.0},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::Narrow, framesize: 10.0},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::Narrow, framesize: 20.0},
-
-    Config {mode: Mode::CELT, bandwith: Bandwidth::Wide, framesize: 2.5},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::Wide, framesize: 5.0},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::Wide, framesize: 10.0},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::Wide, framesize: 20.0},
-
-    Config {mode: Mode::CELT, bandwith: Bandwidth::SuperWide, framesize: 2.5},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::SuperWide, framesize: 5.0},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::SuperWide, framesize: 10.0},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::SuperWide, framesize: 20.0},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::Narrow, framesize: FrameSize::Ms2_5},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::Narrow, framesize: FrameSize::Ms5},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::Narrow, framesize: FrameSize::Ms10},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::Narrow, framesize: FrameSize::Ms20},
+
+    Config {mode: Mode::CELT, bandwith: Bandwidth::Wide, framesize: FrameSize::Ms2_5},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::Wide, framesize: FrameSize::Ms5},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::Wide, framesize: FrameSize::Ms10},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::Wide, framesize: FrameSize::Ms20},
+
+    Config {mode: Mode::CELT, bandwith: Bandwidth::SuperWide, framesize: FrameSize::Ms2_5},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::SuperWide, framesize: FrameSize::Ms5},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::SuperWide, framesize: FrameSize::Ms10},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::SuperWide, framesize: FrameSize::Ms20},
     
-    Config {mode: Mode::CELT, bandwith: Bandwidth::FullBand, framesize: 2.5},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::FullBand, framesize: 5.0},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::FullBand, framesize: 10.0},
-    Config {mode: Mode::CELT, bandwith: Bandwidth::FullBand, framesize: 20.0}
+    Config {mode: Mode::CELT, bandwith: Bandwidth::FullBand, framesize: FrameSize::Ms2_5},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::FullBand, framesize: FrameSize::Ms5},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::FullBand, framesize: FrameSize::Ms10},
+    Config {mode: Mode::CELT, bandwith: Bandwidth::FullBand, framesize: FrameSize::Ms20}
 ];
+
+/// Configuration of the `config` field `index` of a TOC byte, [`None`] if it is
+/// out of range (more than 31).
+pub const fn config_for_index(index: u8) -> Option<Config> {
+    if index < OPUS_CONFIG_TABLE.len() as u8 {
+        Some(OPUS_CONFIG_TABLE[index as usize])
+    } else {
+        None
+    }
+}
+
+/// Mode, bandwidth and frame size in milliseconds of the `config` field `index`
+/// of a TOC byte, [`None`] if it is out of range (more than 31).
+pub fn decompose_index(index: u8) -> Option<(Mode, Bandwidth, f32)> {
+    config_for_index(index).map(|config| (config.mode, config.bandwith, config.framesize()))
+}
+
+/// Reverse lookup of [`OPUS_CONFIG_TABLE`], see [`Config::to_toc_index`].
+pub fn config_index(config: &Config) -> Option<u8> {
+    config.to_toc_index()
+}
+
+/// Longest duration of audio a packet may carry.
+pub const MAX_PACKET_DURATION_MS: f32 = 120.0;
+
+/// [`MAX_PACKET_DURATION_MS`] in microseconds.
+pub(crate) const MAX_PACKET_DURATION_US: u32 = MAX_PACKET_DURATION_MS as u32 * 1000;
+
+/// Maximum number of frames of `framesize` a packet can carry within 120 ms, i.e.
+/// 48, 24, 12, 6, 3 and 2 frames of 2.5, 5, 10, 20, 40 and 60 ms respectively.
+pub const fn max_frames(framesize: FrameSize) -> usize {
+    (MAX_PACKET_DURATION_US / framesize.as_micros()) as usize
+}
+
+/// Encodes a TOC byte, the inverse of the TOC decoding done by the parser.
+/// 
+/// Returns [`None`] if the configuration is not one of [`OPUS_CONFIG_TABLE`].
+pub fn encode_toc(frame_config: &FrameConfig, code: Code) -> Option<u8> {
+    let config = frame_config.config.to_toc_index()?;
+
+    //  0 1 2 3 4 5 6 7
+    // +-+-+-+-+-+-+-+-+
+    // | config  |s| c |
+    // +-+-+-+-+-+-+-+-+
+    Some(config << 3 | (frame_config.is_stereo as u8) << 2 | code as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bandwidth_sample_rate() {
+        assert_eq!(Bandwidth::Narrow.sample_rate(), 8000);
+        assert_eq!(Bandwidth::Medium.sample_rate(), 12000);
+        assert_eq!(Bandwidth::Wide.sample_rate(), 16000);
+        assert_eq!(Bandwidth::SuperWide.sample_rate(), 24000);
+        assert_eq!(Bandwidth::FullBand.sample_rate(), 48000);
+    }
+
+    #[test]
+    fn encode_toc_round_trip() {
+        for toc in 0..=255u8 {
+            let frame_config = FrameConfig::from_toc(toc);
+
+            assert_eq!(encode_toc(&frame_config, Code::from_toc(toc)), Some(toc));
+        }
+    }
+
+    #[test]
+    fn channels() {
+        assert_eq!(FrameConfig::from_toc(0x00).channels(), 1);
+        assert_eq!(FrameConfig::from_toc(0x04).channels(), 2);
+        assert_eq!(FrameConfig::default().channels(), 2);
+    }
+
+    #[test]
+    fn toc_index_round_trip() {
+        for (index, config) in OPUS_CONFIG_TABLE.iter().enumerate() {
+            assert_eq!(config.to_toc_index(), Some(index as u8));
+        }
+    }
+
+    #[test]
+    fn framesizes() {
+        let framesizes = [
+            (FrameSize::Ms2_5, 2.5), (FrameSize::Ms5, 5.0), (FrameSize::Ms10, 10.0),
+            (FrameSize::Ms20, 20.0), (FrameSize::Ms40, 40.0), (FrameSize::Ms60, 60.0)
+        ];
+
+        for (framesize, millis) in framesizes {
+            assert_eq!(framesize.as_millis(), millis);
+            assert_eq!(FrameSize::try_from(millis), Ok(framesize));
+        }
+
+        assert_eq!(FrameSize::try_from(7.5), Err(Error::InvalidConfig));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn framesize_serde() {
+        assert_eq!(serde_json::to_string(&FrameSize::Ms2_5).unwrap(), "2.5");
+        assert_eq!(serde_json::from_str::<FrameSize>("60.0").unwrap(), FrameSize::Ms60);
+        assert!(serde_json::from_str::<FrameSize>("7.5").is_err());
+    }
+
+    #[test]
+    fn samples_per_frame() {
+        let samples = [
+            (FrameSize::Ms2_5, 120), (FrameSize::Ms5, 240), (FrameSize::Ms10, 480),
+            (FrameSize::Ms20, 960), (FrameSize::Ms40, 1920), (FrameSize::Ms60, 2880)
+        ];
+
+        for (framesize, count) in samples {
+            let config = Config { framesize, ..Config::default() };
+
+            assert_eq!(config.samples_per_frame(48000), count);
+            assert_eq!(config.samples_per_frame(8000), count / 6);
+        }
+    }
+
+    #[test]
+    fn max_frames_per_framesize() {
+        let caps = [
+            (FrameSize::Ms2_5, 48), (FrameSize::Ms5, 24), (FrameSize::Ms10, 12),
+            (FrameSize::Ms20, 6), (FrameSize::Ms40, 3), (FrameSize::Ms60, 2)
+        ];
+
+        for (framesize, cap) in caps {
+            assert_eq!(max_frames(framesize), cap);
+        }
+    }
+
+    #[test]
+    fn config_for_index_const() {
+        const FIRST: Option<Config> = config_for_index(0);
+
+        assert_eq!(FIRST, Some(Config { mode: Mode::SILK, bandwith: Bandwidth::Narrow, framesize: FrameSize::Ms10 }));
+        assert_eq!(config_for_index(31), Some(Config::default()));
+        assert_eq!(config_for_index(32), None);
+        assert_eq!(decompose_index(16), Some((Mode::CELT, Bandwidth::Narrow, 2.5)));
+    }
+
+    #[test]
+    fn known_toc_bytes() {
+        let tocs = [
+            (0x4b, Mode::SILK, Bandwidth::Wide, FrameSize::Ms20, false, Code::Code3),
+            (0x7d, Mode::Hybrid, Bandwidth::FullBand, FrameSize::Ms20, true, Code::Code1),
+            (0x82, Mode::CELT, Bandwidth::Narrow, FrameSize::Ms2_5, false, Code::Code2),
+            (0xfc, Mode::CELT, Bandwidth::FullBand, FrameSize::Ms20, true, Code::Code0),
+        ];
+
+        for (toc, mode, bandwith, framesize, is_stereo, code) in tocs {
+            let frame_config = FrameConfig::from_toc(toc);
+
+            assert_eq!(frame_config.config, Config { mode, bandwith, framesize });
+            assert_eq!(frame_config.is_stereo, is_stereo);
+            assert_eq!(Code::from_toc(toc), code);
+        }
+    }
+
+    #[test]
+    fn frame_samples_per_rate() {
+        let config = Config { framesize: FrameSize::Ms20, ..Config::default() };
+
+        for (rate, samples) in [(8000, 160), (12000, 240), (16000, 320), (24000, 480), (48000, 960)] {
+            assert_eq!(config.frame_samples(rate), samples);
+        }
+
+        assert_eq!(Config::max_frame_samples(), 2880);
+    }
+
+    #[test]
+    fn max_frames_per_config() {
+        for config in &OPUS_CONFIG_TABLE {
+            let max_frames = config.max_frames();
+
+            assert_eq!(max_frames as f32 * config.framesize(), MAX_PACKET_DURATION_MS);
+            assert_eq!(max_frames, match config.framesize {
+                FrameSize::Ms2_5 => 48,
+                FrameSize::Ms5 => 24,
+                FrameSize::Ms10 => 12,
+                FrameSize::Ms20 => 6,
+                FrameSize::Ms40 => 3,
+                FrameSize::Ms60 => 2
+            });
+        }
+    }
+
+
+    #[test]
+    fn new_round_trip() {
+        for config in &OPUS_CONFIG_TABLE {
+            assert_eq!(Config::new(config.mode, config.bandwith, config.framesize()), Some(*config));
+        }
+
+        // SILK frames are at least 10 ms, Hybrid is at least super-wide-band.
+        assert_eq!(Config::new(Mode::SILK, Bandwidth::Wide, 2.5), None);
+        assert_eq!(Config::new(Mode::Hybrid, Bandwidth::Narrow, 20.0), None);
+        assert_eq!(Config::new(Mode::CELT, Bandwidth::FullBand, 7.5), None);
+    }
+}
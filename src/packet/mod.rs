@@ -2,15 +2,18 @@
 //! 
 //! Packets contain a set of frames either of same (usually CBR) or variable size.
 //! For variable size frames (usually VBR), variably-sized length fields are present
-//! for all but the last frame, for such it becomes non-delimiting. This parser cannot
-//! parse the format described in [RFC 6716, Appendix B][1] wherein a length field
-//! exists for the aforementioned last frame. Therefore, for Opus multistream (e.g.
-//! with [Ogg encapsulation][2]) (upto 255 channels) cannot be parsed thru this.
+//! for all but the last frame, for such it becomes non-delimiting. The format described
+//! in [RFC 6716, Appendix B][1] wherein a length field exists for the aforementioned
+//! last frame is parsed separately thru [`parser::parse_self_delimiting`], as used by
+//! Opus multistream (e.g. with [Ogg encapsulation][2]) (upto 255 channels).
 //! 
 //! [1]: https://datatracker.ietf.org/doc/html/rfc6716#appendix-B
-//! [2]: https://datatracker.ietf.org/doc/html/rfc784
+//! [2]: https://datatracker.ietf.org/doc/html/rfc7845
 
 pub mod parser;
 pub mod coder;
 pub mod config;
-pub(crate) mod utils;
\ No newline at end of file
+#[cfg(feature = "std")]
+pub mod stream;
+pub(crate) mod utils;
+pub(crate) mod range;
\ No newline at end of file
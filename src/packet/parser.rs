@@ -1,11 +1,20 @@
-use bitvec::prelude::*;
+use core::fmt;
+use core::ops::Range;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 
 pub use super::config::*;
+use super::range::RangeDecoder;
 use super::utils::parse_frame_length;
 
 /// Code or type of packet. Primarily dictates the layout of frames inside a packet.
 /// And optionally padding data if any (only for Code 3 packets).
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// 
+/// Serialized as the code number (0-3) as is on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", try_from = "u8"))]
 pub enum Code {
     /// One frame.
     Code0 = 0,
@@ -16,25 +25,47 @@ pub enum Code {
     /// Multiple frames (upto 120 ms total).
     /// 
     /// Static length per frame if CBR else variable length and VBR.
-    Code3 = 4,
+    Code3 = 3,
 }
 
-impl From<u8> for Code {
-    fn from(value: u8) -> Self {
-        match value {
+impl Code {
+    /// Code of a TOC byte, i.e. its lowest 2 bits.
+    pub const fn from_toc(toc: u8) -> Self {
+        match toc & 0x3 {
             0 => Code::Code0,
             1 => Code::Code1,
             2 => Code::Code2,
-            3 => Code::Code3,
-            _ => unreachable!()
+            _ => Code::Code3
+        }
+    }
+}
+
+impl TryFrom<u8> for Code {
+    type Error = Error;
+
+    /// Converts a code number, failing with [`Error::InvalidCode`] if it is
+    /// more than 3. See [`Code::from_toc`] for masking it out of a TOC byte.
+    fn try_from(value: u8) -> Result<Self, Error> {
+        match value {
+            0..=3 => Ok(Code::from_toc(value)),
+            _ => Err(Error::InvalidCode)
         }
     }
 }
 
+impl From<Code> for u8 {
+    fn from(code: Code) -> Self {
+        code as u8
+    }
+}
+
 /// Statistical and internal information about the parsed packet.
 /// See [`fn parse`] for its usage.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Info {
+    /// The TOC byte as is, from which the rest of the fields are decoded.
+    pub toc: u8,
     /// Global configuration for all frames in the packet.
     pub frame_config: FrameConfig,
     /// Usage of VBR, [`Some(true)`] if VBR or [`Some(false)`] if CBR or, [`None`] if
@@ -42,13 +73,110 @@ pub struct Info {
     pub is_vbr: Option<bool>,
     /// (Non-zero) number of frames that exist in this packet.
     /// 
-    /// If it not a Code 3 packet or, if `strict` is enabled and is a Code 3 packet
+    /// If it not a Code 3 packet or, if parsed strictly and is a Code 3 packet
     /// it will be non-zero otherwise might be zero (i.e no frames are added).
     pub num_frames: usize,
     /// Code or type of packet.
     pub code_no: Code,
 }
 
+impl Info {
+    /// Operating mode of the frames.
+    pub fn mode(&self) -> Mode {
+        self.frame_config.config.mode
+    }
+
+    /// Bandwidth of the frames.
+    pub fn bandwidth(&self) -> Bandwidth {
+        self.frame_config.config.bandwith
+    }
+
+    /// Number of channels of the frames, 1 or 2.
+    pub fn channels(&self) -> u8 {
+        self.frame_config.channels()
+    }
+
+    /// Whether the frames of both packets share the parameters required of frames
+    /// of a single packet (mode, bandwidth, frame size and channel count), unlike
+    /// [`PartialEq`] which also compares the framing of the packets.
+    pub fn same_stream_config(&self, other: &Info) -> bool {
+        self.frame_config == other.frame_config
+    }
+
+    /// Total duration of audio in the packet in milliseconds, at most 120 ms.
+    /// 
+    /// Zero for (non-strictly parsed) Code 3 packets without any frames.
+    pub fn duration_ms(&self) -> f32 {
+        self.duration_us() as f32 / 1000.0
+    }
+
+    /// Total duration of audio in the packet in microseconds, at most 120000 µs.
+    pub fn duration_us(&self) -> u32 {
+        (self.frame_config.config.framesize.as_micros() as usize * self.num_frames).min(MAX_PACKET_DURATION_US as usize) as u32
+    }
+
+    /// Whether a frame is empty, which during discontinuous transmission (DTX)
+    /// signals the decoder to conceal the missing audio (e.g. comfort noise).
+    pub fn is_dtx_frame(frame: &[u8]) -> bool {
+        frame.is_empty()
+    }
+
+    /// Number of PCM samples (per channel) the packet decodes to at `sample_rate`.
+    /// 
+    /// Fractional samples are truncated, which never happens at the rates Opus
+    /// decodes to (8, 12, 16, 24 and 48 kHz), e.g. a 2.5 ms frame is exactly 120
+    /// samples at 48 kHz.
+    pub fn sample_count(&self, sample_rate: u32) -> usize {
+        (self.duration_us() as u64 * sample_rate as u64 / 1_000_000) as usize
+    }
+
+    /// Instantaneous bitrate of the packet in bits per second, had it `payload_bytes`
+    /// bytes (e.g. [`Internal::payload_bytes`] or the whole packet), rather than a running
+    /// average over a stream. Zero for packets without any audio.
+    pub fn bitrate_bps(&self, payload_bytes: usize) -> u32 {
+        match self.duration_us() {
+            0 => 0,
+            duration_us => (payload_bytes as u64 * 8 * 1_000_000 / duration_us as u64) as u32
+        }
+    }
+
+    /// Duration of the packet in samples at `rate`, typically 48 kHz for
+    /// buffering and jitter calculations. See [`Info::sample_count`].
+    pub fn duration_samples(&self, rate: u32) -> usize {
+        self.sample_count(rate)
+    }
+}
+
+impl fmt::Display for Info {
+    /// Summarizes the packet, e.g. `SILK/WB 20ms x3 stereo VBR code3`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bandwidth = match self.bandwidth() {
+            Bandwidth::Narrow => "NB",
+            Bandwidth::Medium => "MB",
+            Bandwidth::Wide => "WB",
+            Bandwidth::SuperWide => "SWB",
+            Bandwidth::FullBand => "FB"
+        };
+
+        // Code 0 and 1 frames are equal in length, Code 2 frames need not be.
+        let is_vbr = match self.code_no {
+            Code::Code0 | Code::Code1 => false,
+            Code::Code2 => true,
+            Code::Code3 => self.is_vbr == Some(true)
+        };
+
+        write!(f, "{:?}/{} {}ms x{} {} {} code{}",
+            self.mode(),
+            bandwidth,
+            self.frame_config.config.framesize(),
+            self.num_frames,
+            if self.channels() == 2 { "stereo" } else { "mono" },
+            if is_vbr { "VBR" } else { "CBR" },
+            self.code_no as u8
+        )
+    }
+}
+
 /// Parser's exported internal information.
 pub struct Internal<'a> {
     /// Statistical information about the packet.
@@ -58,189 +186,559 @@ pub struct Internal<'a> {
     /// than a single byte carrying the additional padding bytes or, [`Some(1, None)`]
     /// if only the padding byte (which is set to zero) exists, making total padding
     /// a byte long or, [`None`] if the packet is unpadded.
-    pub padding: Option<(usize, Option<&'a [u8]>)>
+    pub padding: Option<(usize, Option<&'a [u8]>)>,
+
+    /// Number of bytes of the packet buffer that were parsed. Always the whole
+    /// buffer for non-self-delimiting packets.
+    pub consumed: usize,
+
+    /// Number of empty (zero-length) frames, signaling discontinuous transmission
+    /// (DTX) of silence or comfort noise.
+    pub num_dtx_frames: usize,
+
+    /// Number of compressed audio bytes, i.e. the sum of the frame lengths
+    /// excluding the TOC, frame lengths and padding.
+    pub payload_bytes: usize
+}
+
+impl Internal<'_> {
+    /// Whether all frames of the packet are empty, i.e. it carries no audio
+    /// but keeps the (silent) stream alive.
+    pub fn is_dtx(&self) -> bool {
+        self.num_dtx_frames == self.info.num_frames
+    }
+
+    /// Whether any frame of the packet is empty.
+    pub fn has_dtx(&self) -> bool {
+        self.num_dtx_frames > 0
+    }
+
+
+    /// Instantaneous bitrate of the compressed audio in bits per second, see
+    /// [`Info::bitrate_bps`].
+    pub fn bitrate_bps(&self) -> u32 {
+        self.info.bitrate_bps(self.payload_bytes)
+    }
+
+    /// Instantaneous bitrate of the packet excluding padding in bits per second,
+    /// i.e. what a padded (e.g. constant bitrate) stream would take without it.
+    pub fn effective_bitrate_bps(&self) -> u32 {
+        let padding = self.padding.map_or(0, |(padding, _)| padding);
+
+        self.info.bitrate_bps(self.consumed - padding)
+    }
+
+    /// Number of bytes spent on framing rather than audio, i.e. the TOC, frame
+    /// count, frame lengths and padding.
+    pub fn overhead_bytes(&self) -> usize {
+        self.consumed - self.payload_bytes
+    }
 }
 
 /// An error that occured during parsing, volating one of the
 /// Opus packet handling rules defined in [RFC 6716, Sec 3.4][1].
 /// 
+/// New variants may be added without a major version bump, so matching on it
+/// must include a wildcard (`_ =>`) arm.
+/// 
 /// [1]: https://datatracker.ietf.org/doc/html/rfc6716#section-3.4
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum Error {
     /// No TOC exists in the packet.
     NoTOC,
     /// Frame is too big (more than 1275 bytes).
     /// 
-    /// Note: Only thrown if `strict` is enabled.
+    /// Note: Only thrown if [`ParseOptions::max_frame_bytes`] is exceeded.
     FrameTooBig,
-    /// Integer even-length for *Code 1* packets.
-    NonOddLength,
+    /// Compressed data of a *Code 1* packet (all but the TOC byte) is of odd
+    /// length, thus cannot be split into two equal-sized frames.
+    UnevenCode1,
     /// Packet is too small to parse correctly.
     /// 
-    /// Note: Thrown only in critical conditions, unless `strict` is enabled.
+    /// Note: Thrown only in critical conditions, unless [`ParseOptions::strict`] is set.
     PacketTooSmall,
     /// Specified packet length overflows the packet size.
-    /// 
-    /// Note: Only thrown if `strict` feature is enabled.
     LengthOverflow,
     /// Code 3 packet exceeding maximum duration past 120ms.
     /// 
-    /// Note: Only thrown if `strict` feature is enabled.
+    /// Note: Only thrown if [`ParseOptions::max_duration_us`] is exceeded.
     TooMuchAudio,
     /// Non frame-count integer multiple remainer byte count.
     NonMultipleRemainder,
     /// Code 3 packet having zero audio frames.
+    /// 
+    /// Note: Only thrown by the parser if [`ParseOptions::strict`] is set.
     NoAudio,
+    /// Code number is not one of 0, 1, 2 or 3.
+    InvalidCode,
+    /// Configuration is not one of [`OPUS_CONFIG_TABLE`].
+    InvalidConfig,
+    /// Frames cannot be laid out with the requested code.
+    CodeMismatch,
+    /// CBR requested for frames of different lengths.
+    NonUniformFrames,
+    /// Packets of different configurations cannot be merged.
+    ConfigMismatch,
+    /// Padding has non-zero bytes.
+    /// 
+    /// Note: Only thrown if [`ParseOptions::validate_padding`] is set.
+    NonZeroPadding,
+    /// Packet has no SILK frame header, being CELT-only or its first frame empty
+    /// (DTX).
+    NoSilk,
+    /// Packet has more frames than the buffer passed to [`parse_into`] holds.
+    TooManyFrames,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Error::NoTOC => "packet has no TOC byte",
+            Error::FrameTooBig => "frame is larger than 1275 bytes",
+            Error::UnevenCode1 => "Code 1 packet has an odd number of compressed bytes",
+            Error::PacketTooSmall => "packet is too small to parse",
+            Error::LengthOverflow => "length overflows the packet",
+            Error::TooMuchAudio => "packet holds more than 120 ms of audio",
+            Error::NonMultipleRemainder => "CBR frame data is not a multiple of the frame count",
+            Error::NoAudio => "Code 3 packet has no frames",
+            Error::InvalidCode => "code number is more than 3",
+            Error::InvalidConfig => "configuration is not representable in a TOC byte",
+            Error::CodeMismatch => "frames do not fit the requested code",
+            Error::NonUniformFrames => "CBR requested but frames differ in length",
+            Error::ConfigMismatch => "packets differ in configuration",
+            Error::NonZeroPadding => "padding has non-zero bytes",
+            Error::NoSilk => "packet has no SILK frame header",
+            Error::TooManyFrames => "packet has more frames than the buffer holds",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Validations the parser performs beyond the bare minimum needed to lay out
+/// frames, selectable at runtime.
+/// 
+/// New options may be added without a major version bump, so it can only be
+/// built with [`ParseOptions::builder`] or [`Default`] outside of this crate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// Reject packets that are well-formed only by leniency, e.g. Code 3 packets
+    /// without any frames.
+    pub strict: bool,
+    /// Largest frame accepted in bytes, 1275 as per the RFC.
+    pub max_frame_bytes: usize,
+    /// Longest packet accepted in microseconds, 120 ms as per the RFC.
+    pub max_duration_us: u32,
+    /// Reject packets with non-zero padding, see [`Error::NonZeroPadding`].
+    pub validate_padding: bool,
+}
+
+impl Default for ParseOptions {
+    /// Strict with RFC limits if the `strict` feature is enabled, otherwise
+    /// lenient without any limits. Padding is validated if the `validate_padding`
+    /// feature is enabled.
+    fn default() -> Self {
+        if cfg!(feature = "strict") {
+            Self {
+                strict: true,
+                max_frame_bytes: 1275,
+                max_duration_us: MAX_PACKET_DURATION_US,
+                validate_padding: cfg!(feature = "validate_padding")
+            }
+        } else {
+            Self {
+                strict: false,
+                max_frame_bytes: usize::MAX,
+                max_duration_us: u32::MAX,
+                validate_padding: cfg!(feature = "validate_padding")
+            }
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Builder of options, starting out lenient without any limits regardless of
+    /// the crate features.
+    pub fn builder() -> ParseOptionsBuilder {
+        ParseOptionsBuilder {
+            options: Self {
+                strict: false,
+                max_frame_bytes: usize::MAX,
+                max_duration_us: u32::MAX,
+                validate_padding: false
+            }
+        }
+    }
+}
+
+/// Builder of [`ParseOptions`], see [`ParseOptions::builder`].
+#[derive(Debug, Clone, Copy)]
+pub struct ParseOptionsBuilder {
+    options: ParseOptions,
+}
+
+impl ParseOptionsBuilder {
+    /// See [`ParseOptions::strict`].
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.options.strict = strict;
+        self
+    }
+
+    /// See [`ParseOptions::max_frame_bytes`].
+    pub fn max_frame_bytes(mut self, max_frame_bytes: usize) -> Self {
+        self.options.max_frame_bytes = max_frame_bytes;
+        self
+    }
+
+    /// See [`ParseOptions::max_duration_us`].
+    pub fn max_duration_us(mut self, max_duration_us: u32) -> Self {
+        self.options.max_duration_us = max_duration_us;
+        self
+    }
+
+    /// Longest packet accepted in milliseconds, rounded to whole microseconds
+    /// (saturating, e.g. infinity lifts the limit).
+    pub fn max_duration_ms(self, max_duration_ms: f32) -> Self {
+        self.max_duration_us((max_duration_ms * 1000.0 + 0.5) as u32)
+    }
+
+    /// See [`ParseOptions::validate_padding`].
+    pub fn validate_padding(mut self, validate_padding: bool) -> Self {
+        self.options.validate_padding = validate_padding;
+        self
+    }
+
+    /// Finishes building the options.
+    pub fn build(self) -> ParseOptions {
+        self.options
+    }
+}
+
+/// Receiver of the frames found by the parser, in order.
+pub trait FrameSink<'a> {
+    /// Receives the next frame.
+    fn push(&mut self, frame: &'a [u8]);
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> FrameSink<'a> for Vec<&'a [u8]> {
+    fn push(&mut self, frame: &'a [u8]) {
+        Vec::push(self, frame);
+    }
 }
 
 /// Parses a (semi) well-formed non-self-delemiting Opus packets, pushing frames to
-/// a vector of parsed frames and returning statistical and select internal data.
-pub fn parse<'vec, 'pkt: 'vec>(
-    frames: &'vec mut Vec<&'pkt [u8]>, 
+/// a sink (e.g. a vector) of parsed frames and returning statistical and select
+/// internal data.
+/// 
+/// Empty frames (DTX) are pushed as empty slices, see [`Info::is_dtx_frame`].
+pub fn parse<'pkt>(
+    frames: &mut impl FrameSink<'pkt>, 
+    packet: &'pkt [u8]) -> Result<Internal<'pkt>, Error>
+{
+    parse_with_options(frames, packet, &ParseOptions::default())
+}
+
+/// Parses a (semi) well-formed non-self-delemiting Opus packets akin to [`parse`],
+/// validating it as per `opts` rather than the crate features.
+pub fn parse_with_options<'pkt>(
+    frames: &mut impl FrameSink<'pkt>, 
+    packet: &'pkt [u8],
+    opts: &ParseOptions) -> Result<Internal<'pkt>, Error>
+{
+    parse_impl(|frame| frames.push(&packet[frame]), packet, false, opts)
+}
+
+/// Validates a non-self-delimiting Opus packet without retaining its frames,
+/// strictly and with RFC limits regardless of the crate features. Padding is
+/// validated only if the `validate_padding` feature is enabled.
+pub fn validate(packet: &[u8]) -> Result<Info, Error> {
+    let opts = ParseOptions {
+        strict: true,
+        max_frame_bytes: 1275,
+        max_duration_us: MAX_PACKET_DURATION_US,
+        ..ParseOptions::default()
+    };
+
+    Ok(parse_impl(|_| {}, packet, false, &opts)?.info)
+}
+
+/// Parses a (semi) well-formed non-self-delemiting Opus packets akin to [`parse`],
+/// but pushes the byte range of each frame within `packet` instead of a slice.
+#[cfg(feature = "alloc")]
+pub fn parse_ranges<'pkt>(
+    frames: &mut Vec<Range<usize>>, 
+    packet: &'pkt [u8]) -> Result<Internal<'pkt>, Error>
+{
+    parse_impl(|frame| frames.push(frame), packet, false, &ParseOptions::default())
+}
+
+/// Parses a (semi) well-formed non-self-delemiting Opus packets akin to [`parse`],
+/// but pushes each frame along with the offset of its first byte within `packet`.
+#[cfg(feature = "alloc")]
+pub fn parse_with_offsets<'pkt>(
+    frames: &mut Vec<(usize, &'pkt [u8])>, 
+    packet: &'pkt [u8]) -> Result<Internal<'pkt>, Error>
+{
+    parse_impl(|frame| frames.push((frame.start, &packet[frame])), packet, false, &ParseOptions::default())
+}
+
+/// Parses a (semi) well-formed non-self-delemiting Opus packets akin to [`parse`],
+/// but pushes each frame along with the width (1 or 2 bytes) of its length field,
+/// zero for frames without one (Code 0, Code 1, CBR Code 3 and the last frame).
+/// Along with the frame lengths this accounts for [`Internal::overhead_bytes`].
+#[cfg(feature = "alloc")]
+pub fn parse_with_lengths<'pkt>(
+    frames: &mut Vec<(u8, &'pkt [u8])>, 
     packet: &'pkt [u8]) -> Result<Internal<'pkt>, Error>
 {
-    if packet.len() < 1 {
-        return Err(Error::NoTOC);
+    let mut iter = FrameIter::new(packet)?;
+
+    while let Some(frame) = iter.next_range() {
+        frames.push((iter.len_width, &packet[frame?]));
+    }
+
+    iter.finish()
+}
+
+/// Parses a (semi) well-formed non-self-delemiting Opus packets akin to [`parse`],
+/// but writes the frames into a caller-provided buffer without allocating,
+/// returning the number of frames written along. 48 slots suffice for any packet
+/// (48 frames of 2.5 ms).
+/// 
+/// Fails with [`Error::TooManyFrames`] without writing anything if the packet
+/// has more frames than `frames` holds.
+pub fn parse_into<'pkt>(
+    frames: &mut [Option<&'pkt [u8]>],
+    packet: &'pkt [u8]) -> Result<(Internal<'pkt>, usize), Error>
+{
+    let mut iter = FrameIter::new(packet)?;
+    let num_frames = iter.info().num_frames;
+
+    if num_frames > frames.len() {
+        return Err(Error::TooManyFrames);
     }
 
-    let toc;
-    let config;
-    let is_stereo;
-    let frame_config;
-    let code_no;
+    for slot in &mut frames[..num_frames] {
+        *slot = iter.next_range().transpose()?.map(|frame| &packet[frame]);
+    }
 
-    let mut is_vbr;
-    let mut padding;
+    Ok((iter.finish()?, num_frames))
+}
 
-    //  0 1 2 3 4 5 6 7
-    // +-+-+-+-+-+-+-+-+
-    // | config  |s| c |
-    // +-+-+-+-+-+-+-+-+
-    toc = packet[0].view_bits::<Msb0>();
+/// Information, frames and padding data of a packet parsed by [`parse_owned`].
+#[cfg(feature = "alloc")]
+pub type OwnedPacket = (Info, Vec<Vec<u8>>, Option<Vec<u8>>);
 
-    config = OPUS_CONFIG_TABLE[toc[..5].load::<usize>()];
-    is_stereo = toc[5];
-    frame_config = FrameConfig {config, is_stereo};
-    code_no = toc[6..].load::<u8>();
+/// Parses a (semi) well-formed non-self-delemiting Opus packets akin to [`parse`],
+/// but copies the frames and the padding data into owned buffers, so they may
+/// outlive the packet.
+/// 
+/// It costs an allocation per frame (and the padding) on top of copying all of
+/// the packet, prefer [`parse`] where the packet can be kept around.
+#[cfg(feature = "alloc")]
+pub fn parse_owned(packet: &[u8]) -> Result<OwnedPacket, Error> {
+    let mut frames = Vec::new();
+    let internal = parse(&mut frames, packet)?;
 
-    is_vbr = None;
-    padding = None;
-    
-    match code_no {
-        // Code 0, 1 frame
-        0x0 => {
-            let compressed = &packet[1..];
+    Ok((
+        internal.info,
+        frames.into_iter().map(<[u8]>::to_vec).collect(),
+        internal.padding.and_then(|(_, data)| data).map(<[u8]>::to_vec)
+    ))
+}
 
-            #[cfg(feature = "strict")]
-            if compressed.len() > 1275 {
-                return Err(Error::FrameTooBig);
-            }
+/// Parses a self-delimiting Opus packet as described in [RFC 6716, Appendix B][1],
+/// where the last frame carries an explicit length too. Trailing bytes beyond the
+/// packet are left untouched, [`Internal::consumed`] tells where the next one begins.
+/// 
+/// [1]: https://datatracker.ietf.org/doc/html/rfc6716#appendix-B
+pub fn parse_self_delimiting<'pkt>(
+    frames: &mut impl FrameSink<'pkt>, 
+    packet: &'pkt [u8]) -> Result<Internal<'pkt>, Error>
+{
+    parse_impl(|frame| frames.push(&packet[frame]), packet, true, &ParseOptions::default())
+}
 
-            frames.push(compressed);
-        }
+/// Parses a buffer of back-to-back self-delimiting packets (e.g. the streams of a
+/// multistream packet but the last) until it is wholly consumed, failing if any
+/// trailing bytes do not make up a complete packet.
+/// 
+/// [`Internal::consumed`] of each packet counts from its own first byte, so the
+/// offset of a packet is the sum of those preceding it.
+#[cfg(feature = "alloc")]
+pub fn parse_stream(buf: &[u8]) -> Result<Vec<Internal<'_>>, Error> {
+    let mut packets = Vec::new();
+    let mut pos = 0;
 
-        // Code 1, 2 frames
-        0x1 => {
-            // NOTE: too much strict semantic perhaps.
-            if packet.len() % 2 != 0 {
-                return Err(Error::NonOddLength);
-            }
+    while pos < buf.len() {
+        let internal = parse_impl(|_| {}, &buf[pos..], true, &ParseOptions::default())?;
+
+        pos += internal.consumed;
+        packets.push(internal);
+    }
 
-            let compressed = &packet[1..];
+    Ok(packets)
+}
 
-            // data will be split to two equal sized frames (probably CBR).
-            let (frame_0, frame_1) = compressed.split_at(compressed.len() / 2);
+/// Lazy iterator over the frames of a non-self-delimiting packet, reading each
+/// frame's bounds on demand without collecting them anywhere.
+/// 
+/// The TOC and (for Code 3) the frame count and padding headers are parsed upfront
+/// so the [`Info`] and padding are available before iterating. Iteration stops at
+/// the first malformed frame.
+#[derive(Debug, Clone)]
+pub struct FrameIter<'a> {
+    packet: &'a [u8],
+    info: Info,
+    self_delimiting: bool,
+    options: ParseOptions,
 
-            #[cfg(feature = "strict")]
-            if frame_0.len() > 1275 || frame_1.len() > 1275 {
-                return Err(Error::FrameTooBig);
-            }
+    /// Length shared by all frames (Code 1 and CBR Code 3), otherwise every frame
+    /// has its own length field except the last one of non-self-delimiting packets.
+    frame_len: Option<usize>,
+    /// Position of the next frame length field.
+    len_pos: usize,
+    /// Width of the length field of the last read frame, zero if it had none.
+    len_width: u8,
+    /// Position of the next frame.
+    frame_pos: usize,
+    /// Bound of frame data, where padding (if any) begins in non-self-delimiting packets.
+    end: usize,
+    /// Number of frames yet to be read.
+    remaining: usize,
+    /// Number of empty frames read so far.
+    num_dtx_frames: usize,
+    /// Number of frame bytes read so far.
+    payload_bytes: usize,
+
+    /// Padding header byte count and padding length.
+    pad: Option<(usize, usize)>,
+}
+
+impl<'a> FrameIter<'a> {
+    /// Parses the headers of a (semi) well-formed non-self-delimiting Opus packet,
+    /// deferring the frames to iteration.
+    pub fn new(packet: &'a [u8]) -> Result<Self, Error> {
+        Self::with_options(packet, &ParseOptions::default())
+    }
 
-            frames.push(frame_0);
-            frames.push(frame_1);
+    /// Parses the headers akin to [`FrameIter::new`], validating the packet as per
+    /// `opts` rather than the crate features.
+    pub fn with_options(packet: &'a [u8], opts: &ParseOptions) -> Result<Self, Error> {
+        Self::with_framing(packet, false, opts)
+    }
+
+    pub(crate) fn with_framing(
+        packet: &'a [u8],
+        self_delimiting: bool,
+        opts: &ParseOptions) -> Result<Self, Error>
+    {
+        if packet.is_empty() {
+            return Err(Error::NoTOC);
         }
 
-        // Code 2, 2 frames (var. size)
-        0x2 => {
-            let frame_0_len = parse_frame_length(&packet[1..3]).ok_or(Error::PacketTooSmall)?;
-            let compressed = &packet[frame_0_len.1..];
+        //  0 1 2 3 4 5 6 7
+        // +-+-+-+-+-+-+-+-+
+        // | config  |s| c |
+        // +-+-+-+-+-+-+-+-+
+        let toc = packet[0];
 
-            // offset is needed no more, so redeclare.
-            let frame_0_len = frame_0_len.0;
+        let frame_config = FrameConfig::from_toc(toc);
+        let config = frame_config.config;
+        let code_no = Code::from_toc(toc);
+        let num_frames;
 
-            if compressed.len() < frame_0_len {
-                return Err(Error::LengthOverflow);
+        let mut is_vbr = None;
+        let mut pad = None;
+        let mut len_pos = 1;
+        let mut frame_len = None;
+
+        match code_no {
+            // Code 0, 1 frame
+            Code::Code0 => {
+                num_frames = 1;
             }
 
-            frames.push(&compressed[..frame_0_len]);
+            // Code 1, 2 frames
+            Code::Code1 => {
+                num_frames = 2;
 
-            // second frame, spanning the remaining is too big.
-            #[cfg(feature = "strict")]
-            if compressed.len() - frame_0_len > 1275 {
-                return Err(Error::FrameTooBig);
-            }
+                if self_delimiting {
+                    // a single length field describes both frames.
+                    let frame_len_sd = parse_frame_length(&packet[1..]).ok_or(Error::PacketTooSmall)?;
 
-            frames.push(&packet[frame_0_len..]);
-        },
+                    frame_len = Some(frame_len_sd.0);
+                    len_pos += frame_len_sd.1;
+                } else {
+                    let len_compressed = packet.len() - 1;
 
-        // Code 3, multiple frames (var/const. size)
-        0x3 => {
-            if packet.len() < 2 {
-                return Err(Error::PacketTooSmall);
-            }
+                    // data will be split to two equal sized frames (probably CBR).
+                    if !len_compressed.is_multiple_of(2) {
+                        return Err(Error::UnevenCode1);
+                    }
 
-            //  0 1 2 3 4 5 6 7
-            // +-+-+-+-+-+-+-+-+
-            // |v|p|     M     |
-            // +-+-+-+-+-+-+-+-+
-            let fcb = packet[1].view_bits::<Msb0>();
-
-            let is_pad;
-            let mut n_padb;
-            let mut pad_len;
-            let num_frames;
-
-            is_vbr = Some(fcb[0]);
-            is_pad = fcb[1];
-            num_frames = fcb[2..].load();
-
-            n_padb = is_pad as usize;
-            pad_len = 0;
-            
-            #[cfg(feature = "strict")]
-            if num_frames < 1 {
-                return Err(Error::NoAudio);
+                    frame_len = Some(len_compressed / 2);
+                }
             }
 
-            #[cfg(feature = "strict")]
-            // At maximum a packet can have
-            //
-            //  48 -- 2.5ms frames,
-            //  24 --   5ms frames,
-            //  12 --  10ms frames,
-            //   6 --  20ms frames,
-            //   3 --  40ms frames and
-            //   2 --  60ms frames.
-            if config.framesize * num_frames as f32 > 120.0 {
-                return Err(Error::TooMuchAudio);
+            // Code 2, 2 frames (var. size)
+            Code::Code2 => {
+                num_frames = 2;
             }
 
-            if is_pad {
-                loop {
-                    // When Opus padding is used, the number of bytes of padding is encoded
-                    // in the bytes following the frame count byte.  Values from 0...254
-                    // indicate that 0...254 bytes of padding are included, in addition to
-                    // the byte(s) used to indicate the size of the padding.
-                    let padb = packet[2 + n_padb] as usize;
-                    pad_len += padb;
+            // Code 3, multiple frames (var/const. size)
+            Code::Code3 => {
+                if packet.len() < 2 {
+                    return Err(Error::PacketTooSmall);
+                }
 
-                    if padb != 255 {
-                        break;
-                    }
+                //  0 1 2 3 4 5 6 7
+                // +-+-+-+-+-+-+-+-+
+                // |v|p|     M     |
+                // +-+-+-+-+-+-+-+-+
+                let fcb = packet[1];
+
+                let is_cbr = fcb & 0x80 == 0;
+                let is_pad = fcb & 0x40 != 0;
+                let mut n_padb = 0;
+                let mut pad_len = 0;
+
+                is_vbr = Some(!is_cbr);
+                num_frames = (fcb & 0x3f) as usize;
+                
+                // zero frames are only tolerated leniently, yielding no frames.
+                if opts.strict && num_frames < 1 {
+                    return Err(Error::NoAudio);
+                }
+
+                // At maximum a packet can have 120 ms of audio, see `max_frames`.
+                if config.framesize.as_micros() * num_frames as u32 > opts.max_duration_us {
+                    return Err(Error::TooMuchAudio);
+                }
 
-                    // If the value is 255, then the size of the additional padding is 254 bytes,
-                    // plus the padding value encoded in the next byte.
-                    pad_len -= 1;
+                if is_pad {
+                    loop {
+                        // When Opus padding is used, the number of bytes of padding is encoded
+                        // in the bytes following the frame count byte.  Values from 0...254
+                        // indicate that 0...254 bytes of padding are included, in addition to
+                        // the byte(s) used to indicate the size of the padding.
+                        let padb = *packet.get(2 + n_padb).ok_or(Error::PacketTooSmall)? as usize;
+
+                        n_padb += 1;
+                        pad_len += padb;
+
+                        if padb != 255 {
+                            break;
+                        }
+
+                        // If the value is 255, then the size of the additional padding is 254 bytes,
+                        // plus the padding value encoded in the next byte.
+                        pad_len -= 1;
+                    }
 
                     // Let P (pad_len + n_padb) be the number of header bytes used
                     // to indicate the padding size plus the number of padding bytes
@@ -250,97 +748,1248 @@ pub fn parse<'vec, 'pkt: 'vec>(
                         return Err(Error::LengthOverflow);
                     }
 
-                    n_padb += 1;
+                    pad = Some((n_padb, pad_len));
                 }
-            }
 
-            let pad_pos;
+                len_pos = n_padb + 2;
 
-            // let R=N-2-P be the number of bytes remaining in the packet after subtracting
-            // the (optional) padding.
-            let len_compressed = packet.len().checked_sub(n_padb + pad_len + 2).ok_or(Error::PacketTooSmall)?;
+                // let R=N-2-P be the number of bytes remaining in the packet after subtracting
+                // the (optional) padding.
+                let len_compressed = packet.len().checked_sub(n_padb + pad_len + 2).ok_or(Error::PacketTooSmall)?;
 
-            if let Some(_) = is_vbr {
-                let mut frame_pos = n_padb + 2;
+                // a (lenient) packet without frames has no frame lengths to lay out.
+                if is_cbr && num_frames > 0 {
+                    if self_delimiting {
+                        // a single length field describes all the frames.
+                        let frame_len_sd = parse_frame_length(packet.get(len_pos..).unwrap_or_default()).ok_or(Error::PacketTooSmall)?;
 
-                for _ in 0..num_frames-1 {
-                    let frame_len = parse_frame_length(&packet[frame_pos..]).ok_or(Error::PacketTooSmall)?;
-                    
-                    // frame data begins after length and ends at next boundary.
-                    let frame_off = frame_pos + frame_len.1;
-                    let frame = &packet[frame_off..(frame_off+frame_len.0)];
+                        frame_len = Some(frame_len_sd.0);
+                        len_pos += frame_len_sd.1;
+                    } else {
+                        // for CBR each frame is of R/M length. R MUST be a multiple of M.
+                        if len_compressed % num_frames != 0 {
+                            return Err(Error::NonMultipleRemainder);
+                        }
 
-                    #[cfg(feature = "strict")]
-                    if len_compressed < frame.len() {
-                        return Err(Error::PacketTooSmall)?;
+                        frame_len = Some(len_compressed / num_frames);
                     }
-
-                    frames.push(frame);
-
-                    // set beginning of next frame
-                    frame_pos = frame_off + frame_len.0;
                 }
+            }
+        };
 
-                if len_compressed > frame_pos { 
-                    return Err(Error::PacketTooSmall);
-                }
+        let pad_len = pad.map_or(0, |(_, pad_len)| pad_len);
+        let mut frame_pos = len_pos;
 
-                // remaining bytes belong to the last VBR frame.
-                let frame_len = len_compressed - frame_pos;
+        // length fields of all but the last frame (all frames if self-delimiting)
+        // precede the frame data.
+        if frame_len.is_none() {
+            let num_delimited = if self_delimiting { num_frames } else { num_frames.saturating_sub(1) };
 
-                #[cfg(feature = "strict")]
-                if frame_len > 1275 {
-                    return Err(Error::FrameTooBig);
-                }
+            for _ in 0..num_delimited {
+                let frame_len = parse_frame_length(packet.get(frame_pos..).unwrap_or_default()).ok_or(Error::PacketTooSmall)?;
 
-                frames.push(&packet[frame_pos..frame_pos + frame_len]);
+                frame_pos += frame_len.1;
+            }
+        }
 
-                pad_pos = frame_pos + frame_len;
+        Ok(Self {
+            packet,
+            info: Info {
+                toc,
+                frame_config,
+                code_no,
+                is_vbr,
+                num_frames
+            },
+            self_delimiting,
+            options: *opts,
+            frame_len,
+            len_pos,
+            len_width: 0,
+            frame_pos,
+            end: if self_delimiting {
+                packet.len()
             } else {
-                // for CBR each frame is of R/M length. R MUST be a multiple of M.
-                if len_compressed % num_frames != 0 {
-                    return Err(Error::NonMultipleRemainder);
-                }
-
-                let frame_len = len_compressed / num_frames;
-                let mut frame_pos = n_padb + 2;
+                packet.len().checked_sub(pad_len).ok_or(Error::PacketTooSmall)?
+            },
+            remaining: num_frames,
+            num_dtx_frames: 0,
+            payload_bytes: 0,
+            pad
+        })
+    }
 
-                // all frames have the same length if CBR
-                for _ in 0..num_frames {
-                    frames.push(&packet[frame_pos..frame_pos + frame_len]);
+    /// Statistical information about the packet.
+    pub fn info(&self) -> Info {
+        self.info
+    }
 
-                    frame_pos += frame_len;
-                }
+    /// Opus padding, see [`Internal::padding`].
+    pub fn padding(&self) -> Option<(usize, Option<&'a [u8]>)> {
+        self.pad.map(|(n_padb, pad_len)| (pad_len + n_padb, if pad_len == 0 {
+            None
+        } else {
+            self.packet.get(self.end..self.end + pad_len)
+        }))
+    }
 
-                pad_pos = frame_pos;
+    /// Checks that the padding (if any) is zero-filled like the reference encoder
+    /// does, which the RFC does not mandate.
+    fn validate_padding(&self) -> Result<(), Error> {
+        if let Some((_, Some(padding))) = self.padding() {
+            if padding.iter().any(|&byte| byte != 0) {
+                return Err(Error::NonZeroPadding);
             }
+        }
 
-            #[cfg(feature = "strict")]
-            if packet.len() - pad_pos > pad_len {
-                return Err(Error::PacketTooSmall)?;
-            }
+        Ok(())
+    }
+
+    /// Byte range of the next frame within the packet.
+    fn next_range(&mut self) -> Option<Result<Range<usize>, Error>> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let frame = self.read_range();
+
+        // do not carry on past a malformed frame.
+        self.remaining = if frame.is_ok() { self.remaining - 1 } else { 0 };
+
+        Some(frame)
+    }
+
+    fn read_range(&mut self) -> Result<Range<usize>, Error> {
+        let frame_len;
+
+        self.len_width = 0;
+
+        if !self.self_delimiting && self.remaining == 1 {
+            // remaining bytes belong to the last frame.
+            frame_len = self.end.checked_sub(self.frame_pos).ok_or(Error::LengthOverflow)?;
+        } else if let Some(len) = self.frame_len {
+            frame_len = len;
+        } else {
+            let len = parse_frame_length(self.packet.get(self.len_pos..).unwrap_or_default()).ok_or(Error::PacketTooSmall)?;
+
+            frame_len = len.0;
+            self.len_pos += len.1;
+            self.len_width = len.1 as u8;
+        }
+
+        let frame = self.frame_pos..self.frame_pos + frame_len;
+
+        if frame.end > self.end {
+            return Err(Error::LengthOverflow);
+        }
+
+        if frame.len() > self.options.max_frame_bytes {
+            return Err(Error::FrameTooBig);
+        }
+
+        if frame.is_empty() {
+            self.num_dtx_frames += 1;
+        }
+
+        self.payload_bytes += frame.len();
+
+        self.frame_pos = frame.end;
+
+        Ok(frame)
+    }
+
+    /// Reads all the remaining frames at once if they share a length (Code 1 and
+    /// CBR Code 3), their bounds being known upfront, otherwise reads none.
+    fn read_uniform(&mut self, mut push: impl FnMut(Range<usize>)) -> Result<(), Error> {
+        let frame_len = match self.frame_len {
+            Some(frame_len) if self.remaining > 0 => frame_len,
+            _ => return Ok(())
+        };
+
+        // same checks as reading the frames one by one, in the same order.
+        if self.frame_pos + frame_len > self.end {
+            return Err(Error::LengthOverflow);
+        }
 
-            if is_pad {
-                padding = Some((pad_len + n_padb, if pad_len == 0 {
-                    None
-                } else { 
-                    Some(&packet[pad_pos..]) 
-                }));
+        if frame_len > self.options.max_frame_bytes {
+            return Err(Error::FrameTooBig);
+        }
+
+        let data_len = frame_len * self.remaining;
+
+        if self.frame_pos + data_len > self.end {
+            return Err(Error::LengthOverflow);
+        }
+
+        for i in 0..self.remaining {
+            let frame_pos = self.frame_pos + i * frame_len;
+
+            push(frame_pos..frame_pos + frame_len);
+        }
+
+        if frame_len == 0 {
+            self.num_dtx_frames += self.remaining;
+        }
+
+        self.payload_bytes += data_len;
+        self.frame_pos += data_len;
+        self.len_width = 0;
+        self.remaining = 0;
+
+        Ok(())
+    }
+
+    /// Consumes the remaining frames and yields the parser's internal information.
+    fn finish(mut self) -> Result<Internal<'a>, Error> {
+        while let Some(frame) = self.next_range() {
+            frame?;
+        }
+
+        let consumed;
+
+        if self.self_delimiting {
+            // padding follows the last frame, bytes after it belong to the next packet.
+            self.end = self.frame_pos;
+            consumed = self.end + self.pad.map_or(0, |(_, pad_len)| pad_len);
+
+            if self.packet.len() < consumed {
+                return Err(Error::LengthOverflow);
             }
-        },
+        } else {
+            consumed = self.packet.len();
+        }
 
-        _ => unreachable!()
-    };
+        if self.options.validate_padding {
+            self.validate_padding()?;
+        }
+
+        Ok(Internal {
+            info: self.info,
+            padding: self.padding(),
+            consumed,
+            num_dtx_frames: self.num_dtx_frames,
+            payload_bytes: self.payload_bytes
+        })
+    }
+}
 
-    let num_frames = frames.len();
+impl<'a> Iterator for FrameIter<'a> {
+    type Item = Result<&'a [u8], Error>;
 
-    Ok(Internal {
-        info: Info {
-            frame_config, 
-            code_no: code_no.into(),
-            is_vbr,
-            num_frames
-        },
-        padding
-    })
+    fn next(&mut self) -> Option<Self::Item> {
+        let packet = self.packet;
+
+        self.next_range().map(|frame| frame.map(|frame| &packet[frame]))
+    }
+}
+
+/// Iterates the frames of a (semi) well-formed non-self-delimiting Opus packet
+/// lazily, without collecting them into a vector like [`parse`] does.
+pub fn frames(packet: &[u8]) -> Result<FrameIter<'_>, Error> {
+    FrameIter::new(packet)
+}
+
+/// Total number of PCM samples (per channel) a stream of non-self-delimiting
+/// packets decodes to at `rate`, i.e. the length of the stream without decoding
+/// it. See [`OpusHead::trim_pre_skip`](crate::header::OpusHead::trim_pre_skip)
+/// for discarding the pre-skip of Ogg streams.
+/// 
+/// Only the packet headers are parsed, the first malformed packet fails it.
+pub fn total_samples<'a>(mut packets: impl Iterator<Item = &'a [u8]>, rate: u32) -> Result<u64, Error> {
+    packets.try_fold(0, |total, packet| {
+        Ok(total + FrameIter::new(packet)?.info().sample_count(rate) as u64)
+    })
+}
+
+/// Decodes the TOC byte (and for Code 3 the frame count byte) of a packet alone,
+/// neither validating nor reading any frames.
+pub fn parse_toc(packet: &[u8]) -> Result<Info, Error> {
+    let toc = *packet.first().ok_or(Error::NoTOC)?;
+    let code_no = Code::from_toc(toc);
+
+    let num_frames;
+    let is_vbr;
+
+    match code_no {
+        Code::Code0 => {
+            num_frames = 1;
+            is_vbr = None;
+        }
+
+        Code::Code1 | Code::Code2 => {
+            num_frames = 2;
+            is_vbr = None;
+        }
+
+        Code::Code3 => {
+            //  0 1 2 3 4 5 6 7
+            // +-+-+-+-+-+-+-+-+
+            // |v|p|     M     |
+            // +-+-+-+-+-+-+-+-+
+            let fcb = *packet.get(1).ok_or(Error::PacketTooSmall)?;
+
+            num_frames = (fcb & 0x3f) as usize;
+            is_vbr = Some(fcb & 0x80 != 0);
+        }
+    }
+
+    Ok(Info {
+        toc,
+        frame_config: FrameConfig::from_toc(toc),
+        is_vbr,
+        num_frames,
+        code_no
+    })
+}
+
+/// Number of frames of a packet, 1 for Code 0, 2 for Code 1 and 2 and the frame
+/// count of Code 3, e.g. for preallocating the frames before [`parse`]. Reads only
+/// the TOC and frame count bytes, see [`parse_toc`].
+pub fn frame_count(packet: &[u8]) -> Result<usize, Error> {
+    Ok(parse_toc(packet)?.num_frames)
+}
+
+/// Minimum length of a non-self-delimiting packet as implied by its headers (TOC,
+/// frame count, padding and frame lengths), reading nothing else. A buffer shorter
+/// than it is certainly truncated.
+/// 
+/// The implied length excludes the last frame (or for Code 1 and CBR Code 3, all
+/// frames) whose length is only known from the length of the packet.
+/// 
+/// Fails with [`Error::PacketTooSmall`] if the headers themselves are incomplete.
+pub fn required_len(packet: &[u8]) -> Result<usize, Error> {
+    required_len_impl(packet, false)
+}
+
+/// See [`required_len`], a self-delimiting packet's length is wholly implied by its
+/// headers.
+pub(crate) fn required_len_impl(packet: &[u8], self_delimiting: bool) -> Result<usize, Error> {
+    let toc = packet.first().ok_or(Error::NoTOC)?;
+
+    let mut len_pos = 1;
+    let mut len_data = 0;
+
+    // reads a length field, returning the length.
+    let read_length = |len_pos: &mut usize| -> Result<usize, Error> {
+        let frame_len = parse_frame_length(packet.get(*len_pos..).unwrap_or_default()).ok_or(Error::PacketTooSmall)?;
+
+        *len_pos += frame_len.1;
+
+        Ok(frame_len.0)
+    };
+
+    match Code::from_toc(*toc) {
+        Code::Code0 => {
+            if self_delimiting {
+                len_data += read_length(&mut len_pos)?;
+            }
+        },
+
+        Code::Code1 => {
+            if self_delimiting {
+                len_data += 2 * read_length(&mut len_pos)?;
+            }
+        },
+
+        Code::Code2 => {
+            len_data += read_length(&mut len_pos)?;
+
+            if self_delimiting {
+                len_data += read_length(&mut len_pos)?;
+            }
+        }
+
+        Code::Code3 => {
+            let fcb = *packet.get(1).ok_or(Error::PacketTooSmall)?;
+            let num_frames = (fcb & 0x3f) as usize;
+
+            len_pos += 1;
+
+            // padding flag.
+            if fcb & 0x40 != 0 {
+                loop {
+                    let padb = *packet.get(len_pos).ok_or(Error::PacketTooSmall)? as usize;
+
+                    len_pos += 1;
+                    len_data += padb;
+
+                    if padb != 255 {
+                        break;
+                    }
+
+                    len_data -= 1;
+                }
+            }
+
+            // VBR flag.
+            if fcb & 0x80 != 0 {
+                let num_delimited = if self_delimiting { num_frames } else { num_frames.saturating_sub(1) };
+
+                for _ in 0..num_delimited {
+                    len_data += read_length(&mut len_pos)?;
+                }
+            } else if self_delimiting && num_frames > 0 {
+                len_data += num_frames * read_length(&mut len_pos)?;
+            }
+        }
+    }
+
+    Ok(len_pos + len_data)
+}
+
+/// Whether the first frame of a non-self-delimiting SILK or Hybrid packet carries
+/// low-bitrate redundancy (LBRR) of the previous packet, i.e. in-band FEC from
+/// which a lost packet can be partially recovered.
+/// 
+/// Reads the LBRR flags of the SILK frame header without decoding any audio, see
+/// [RFC 6716, Section 4.2.3][1]. Always `Ok(false)` for CELT-only packets, which
+/// have no such redundancy, as well as for empty (DTX) frames.
+/// 
+/// [1]: https://datatracker.ietf.org/doc/html/rfc6716#section-4.2.3
+pub fn has_fec(packet: &[u8]) -> Result<bool, Error> {
+    match silk_header(packet) {
+        Ok(header) => Ok(header.lbrr.contains(&true)),
+        Err(Error::NoSilk) => Ok(false),
+        Err(err) => Err(err)
+    }
+}
+
+/// iCDF of the joint index of the stereo prediction weights.
+const STEREO_PRED_JOINT_ICDF: [u8; 25] = [
+    249, 247, 246, 245, 244, 234, 210, 202, 201, 200, 197, 174, 82,
+    59, 56, 55, 54, 46, 22, 12, 11, 10, 9, 7, 0
+];
+/// iCDF of the stereo prediction weight indices.
+const UNIFORM3_ICDF: [u8; 3] = [171, 85, 0];
+/// iCDF of the stereo prediction weight offsets.
+const UNIFORM5_ICDF: [u8; 5] = [205, 154, 102, 51, 0];
+/// iCDF of the mid-only flag.
+const ONLY_CODE_MID_ICDF: [u8; 2] = [64, 0];
+
+/// Leading flags of the SILK layer of the first frame of a packet, see
+/// [RFC 6716, Section 4.2.3][1].
+/// 
+/// [1]: https://datatracker.ietf.org/doc/html/rfc6716#section-4.2.3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SilkHeader {
+    /// Number of SILK frames in the Opus frame, 2 for 40 ms and 3 for 60 ms,
+    /// otherwise 1.
+    pub num_silk_frames: u8,
+    /// Voice activity of each SILK frame, for the mid (or mono) and the side
+    /// channel. Only the first `num_silk_frames` flags are meaningful, and those
+    /// of the side channel are unset for mono packets.
+    pub vad: [[bool; 3]; 2],
+    /// Whether LBRR frames are present, for the mid (or mono) and the side channel.
+    pub lbrr: [bool; 2],
+    /// Whether only the mid channel is coded in the first SILK frame of a stereo
+    /// packet, i.e. the side channel is left silent.
+    /// 
+    /// [`None`] for mono packets, as well as if LBRR frames precede the first
+    /// SILK frame, as skipping them takes a full decode.
+    pub mid_only: Option<bool>,
+}
+
+/// Reads the SILK frame header of the first frame of a non-self-delimiting SILK
+/// or Hybrid packet without decoding any audio, a cheap voice activity signal.
+/// 
+/// Fails with [`Error::NoSilk`] for CELT-only packets as well as for empty (DTX)
+/// frames.
+pub fn silk_header(packet: &[u8]) -> Result<SilkHeader, Error> {
+    let mut frames = FrameIter::new(packet)?;
+    let info = frames.info();
+
+    if info.mode() == Mode::CELT {
+        return Err(Error::NoSilk);
+    }
+
+    let frame = match frames.next() {
+        Some(frame) => frame?,
+        None => return Err(Error::NoSilk)
+    };
+
+    if frame.is_empty() {
+        return Err(Error::NoSilk);
+    }
+
+    // 40 and 60 ms Opus frames are made up of multiple 20 ms SILK frames.
+    let num_silk_frames = match info.frame_config.config.framesize {
+        FrameSize::Ms40 => 2,
+        FrameSize::Ms60 => 3,
+        _ => 1
+    };
+
+    let is_stereo = info.channels() == 2;
+    let mut dec = RangeDecoder::new(frame);
+    let mut vad = [[false; 3]; 2];
+    let mut lbrr = [false; 2];
+
+    // for each channel (mid, then side), a VAD flag per SILK frame is followed by
+    // a LBRR flag.
+    for ch in 0..info.channels() as usize {
+        for flag in &mut vad[ch][..num_silk_frames] {
+            *flag = dec.decode_bit_logp(1);
+        }
+
+        lbrr[ch] = dec.decode_bit_logp(1);
+    }
+
+    // the stereo prediction weights of the first SILK frame, followed by the
+    // mid-only flag if the side channel is inactive, see Section 4.2.7.1.
+    let mid_only = if is_stereo && !lbrr.contains(&true) {
+        dec.decode_icdf(&STEREO_PRED_JOINT_ICDF, 8);
+
+        for _ in 0..2 {
+            dec.decode_icdf(&UNIFORM3_ICDF, 8);
+            dec.decode_icdf(&UNIFORM5_ICDF, 8);
+        }
+
+        Some(!vad[1][0] && dec.decode_icdf(&ONLY_CODE_MID_ICDF, 8) == 1)
+    } else {
+        None
+    };
+
+    Ok(SilkHeader {
+        num_silk_frames: num_silk_frames as u8,
+        vad,
+        lbrr,
+        mid_only
+    })
+}
+
+/// A parsed non-self-delimiting packet holding its frames, for parsing once and
+/// querying many times. See [`parse`] for parsing without allocating.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct PacketView<'a> {
+    info: Info,
+    frames: Vec<&'a [u8]>,
+    padding: Option<(usize, Option<&'a [u8]>)>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> PacketView<'a> {
+    /// Parses a (semi) well-formed non-self-delimiting Opus packet.
+    pub fn new(packet: &'a [u8]) -> Result<Self, Error> {
+        let mut frames = Vec::new();
+        let internal = parse(&mut frames, packet)?;
+
+        Ok(Self {
+            info: internal.info,
+            frames,
+            padding: internal.padding
+        })
+    }
+
+    /// Statistical information about the packet.
+    pub fn info(&self) -> Info {
+        self.info
+    }
+
+    /// Frames of the packet, in order.
+    pub fn frames(&self) -> &[&'a [u8]] {
+        &self.frames
+    }
+
+    /// Opus padding, see [`Internal::padding`].
+    pub fn padding(&self) -> Option<(usize, Option<&'a [u8]>)> {
+        self.padding
+    }
+
+    /// Total duration of audio in the packet in milliseconds, see [`Info::duration_ms`].
+    pub fn duration_ms(&self) -> f32 {
+        self.info.duration_ms()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> IntoIterator for PacketView<'a> {
+    type Item = &'a [u8];
+    type IntoIter = alloc::vec::IntoIter<&'a [u8]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.frames.into_iter()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'v, 'a> IntoIterator for &'v PacketView<'a> {
+    type Item = &'a [u8];
+    type IntoIter = core::iter::Copied<core::slice::Iter<'v, &'a [u8]>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.frames.iter().copied()
+    }
+}
+
+/// Walks the frame layout of `packet`, handing the byte range of every frame
+/// to `push` in order.
+fn parse_impl<'pkt>(
+    mut push: impl FnMut(Range<usize>), 
+    packet: &'pkt [u8],
+    self_delimiting: bool,
+    opts: &ParseOptions) -> Result<Internal<'pkt>, Error>
+{
+    let mut frames = FrameIter::with_framing(packet, self_delimiting, opts)?;
+
+    frames.read_uniform(&mut push)?;
+
+    while let Some(frame) = frames.next_range() {
+        push(frame?);
+    }
+
+    frames.finish()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    /// CELT-only full-band 20 ms, mono.
+    const TOC_CELT_20MS: u8 = 31 << 3;
+    /// CELT-only full-band 2.5 ms, mono.
+    const TOC_CELT_2_5MS: u8 = 28 << 3;
+
+    #[test]
+    fn code2_two_byte_length() {
+        // frame 0 of 300 bytes takes a 2-byte length, 252 + 12 * 4.
+        let mut packet = vec![TOC_CELT_20MS | 0x2, 252, 12];
+        packet.extend_from_slice(&[0xaa; 300]);
+        packet.extend_from_slice(&[0x55; 20]);
+
+        let mut frames = Vec::new();
+        let internal = parse(&mut frames, &packet).unwrap();
+
+        assert_eq!(internal.info.code_no, Code::Code2);
+        assert_eq!(internal.info.num_frames, 2);
+        assert_eq!(frames, [&[0xaa; 300][..], &[0x55; 20][..]]);
+    }
+
+    /// Parses a self-delimiting `packet` followed by a trailing byte, returning
+    /// its frames and the number of bytes consumed.
+    fn parse_sd(packet: &[u8]) -> (Vec<&[u8]>, usize) {
+        let mut frames = Vec::new();
+        let consumed = parse_self_delimiting(&mut frames, packet).unwrap().consumed;
+
+        (frames, consumed)
+    }
+
+    #[test]
+    fn self_delimiting_codes() {
+        // Code 0: TOC, N1, frame 1.
+        let (frames, consumed) = parse_sd(&[TOC_CELT_20MS, 2, 0xff, 0xfe, 0x00]);
+        assert_eq!(frames, [[0xff, 0xfe]]);
+        assert_eq!(consumed, 4);
+
+        // Code 1: TOC, N1, two frames of N1 bytes.
+        let (frames, consumed) = parse_sd(&[TOC_CELT_20MS | 0x1, 2, 1, 2, 3, 4, 0x00]);
+        assert_eq!(frames, [[1, 2], [3, 4]]);
+        assert_eq!(consumed, 6);
+
+        // Code 2: TOC, N1, N2, frame 1, frame 2.
+        let (frames, consumed) = parse_sd(&[TOC_CELT_20MS | 0x2, 1, 2, 1, 2, 3, 0x00]);
+        assert_eq!(frames, [&[1][..], &[2, 3][..]]);
+        assert_eq!(consumed, 6);
+
+        // Code 3 CBR: TOC, M, N1, M frames of N1 bytes.
+        let (frames, consumed) = parse_sd(&[TOC_CELT_20MS | 0x3, 0x02, 1, 1, 2, 0x00]);
+        assert_eq!(frames, [[1], [2]]);
+        assert_eq!(consumed, 5);
+
+        // Code 3 VBR: TOC, M, N1, N2, N3, frames 1 to 3.
+        let (frames, consumed) = parse_sd(&[TOC_CELT_20MS | 0x3, 0x83, 1, 0, 2, 1, 2, 3, 0x00]);
+        assert_eq!(frames, [&[1][..], &[][..], &[2, 3][..]]);
+        assert_eq!(consumed, 8);
+    }
+
+    #[test]
+    fn error_display() {
+        let errors = [
+            Error::NoTOC, Error::FrameTooBig, Error::UnevenCode1, Error::PacketTooSmall,
+            Error::LengthOverflow, Error::TooMuchAudio, Error::NonMultipleRemainder,
+            Error::NoAudio, Error::InvalidCode, Error::InvalidConfig, Error::CodeMismatch,
+            Error::NonUniformFrames, Error::ConfigMismatch, Error::NonZeroPadding,
+            Error::NoSilk, Error::TooManyFrames
+        ];
+
+        for error in errors {
+            let error: Box<dyn std::error::Error> = Box::new(error);
+
+            assert!(!error.to_string().is_empty());
+        }
+    }
+
+    #[test]
+    fn code3_cbr_and_vbr() {
+        let cbr = [TOC_CELT_20MS | 0x3, 0x03, 1, 2, 3, 4, 5, 6];
+        let mut frames = Vec::new();
+        let internal = parse(&mut frames, &cbr).unwrap();
+
+        assert_eq!(internal.info.is_vbr, Some(false));
+        assert_eq!(frames, [[1, 2], [3, 4], [5, 6]]);
+
+        // same bytes after the lengths, laid out as 1, 3 and 2 bytes.
+        let vbr = [TOC_CELT_20MS | 0x3, 0x83, 1, 3, 1, 2, 3, 4, 5, 6];
+        let mut frames = Vec::new();
+        let internal = parse(&mut frames, &vbr).unwrap();
+
+        assert_eq!(internal.info.is_vbr, Some(true));
+        assert_eq!(frames, [&[1][..], &[2, 3, 4][..], &[5, 6][..]]);
+    }
+
+    #[test]
+    fn duration() {
+        let info = validate(&[TOC_CELT_20MS, 0xff]).unwrap();
+
+        assert_eq!(info.duration_ms(), 20.0);
+        assert_eq!(info.duration_samples(48000), 960);
+
+        // SILK-only wide-band 40 ms, three frames.
+        let info = validate(&[10 << 3 | 0x3, 0x03, 1, 2, 3]).unwrap();
+
+        assert_eq!(info.duration_ms(), 120.0);
+        assert_eq!(info.duration_samples(48000), 5760);
+        assert_eq!(info.duration_samples(16000), 1920);
+    }
+
+    #[test]
+    fn padding_validation() {
+        let opts = ParseOptions::builder().validate_padding(true).build();
+        let zeroed = [TOC_CELT_20MS | 0x3, 0x41, 2, 0xff, 0, 0];
+        let non_zero = [TOC_CELT_20MS | 0x3, 0x41, 2, 0xff, 0, 1];
+
+        let mut frames = Vec::new();
+        let internal = parse_with_options(&mut frames, &zeroed, &opts).unwrap();
+
+        assert_eq!(internal.padding, Some((3, Some(&[0, 0][..]))));
+        assert_eq!(frames, [[0xff]]);
+        assert_eq!(parse_with_options(&mut Vec::new(), &non_zero, &opts).err(), Some(Error::NonZeroPadding));
+
+        // lenient unless asked for.
+        let opts = ParseOptions::builder().build();
+
+        assert!(parse_with_options(&mut Vec::new(), &non_zero, &opts).is_ok());
+    }
+
+    #[test]
+    fn custom_sink() {
+        /// Keeps only the lengths of frames, not the frames themselves.
+        struct Lengths(Vec<usize>);
+
+        impl FrameSink<'_> for Lengths {
+            fn push(&mut self, frame: &[u8]) {
+                self.0.push(frame.len());
+            }
+        }
+
+        let mut lengths = Lengths(Vec::new());
+
+        parse(&mut lengths, &[TOC_CELT_20MS | 0x3, 0x83, 1, 0, 1, 2, 3, 4]).unwrap();
+
+        assert_eq!(lengths.0, [1, 0, 3]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let info = validate(&[10 << 3 | 0x4 | 0x3, 0x83, 1, 0, 1, 2, 3, 4]).unwrap();
+        let json = serde_json::to_string(&info).unwrap();
+
+        assert_eq!(serde_json::from_str::<Info>(&json).unwrap(), info);
+
+        let config = info.frame_config.config;
+
+        assert_eq!(serde_json::from_str::<Config>(&serde_json::to_string(&config).unwrap()).unwrap(), config);
+        assert_eq!(serde_json::to_string(&Mode::SILK).unwrap(), "\"SILK\"");
+        assert_eq!(serde_json::from_str::<Bandwidth>("\"Wide\"").unwrap(), Bandwidth::Wide);
+    }
+
+    #[test]
+    fn max_duration_boundary() {
+        // CELT-only full-band 2.5 ms, CBR frames of a byte.
+        let mut packet = vec![28 << 3 | 0x3, 48];
+        packet.extend_from_slice(&[0; 48]);
+
+        assert_eq!(validate(&packet).unwrap().duration_us(), 120_000);
+
+        packet[1] = 49;
+        packet.push(0);
+
+        assert_eq!(validate(&packet), Err(Error::TooMuchAudio));
+    }
+
+    #[test]
+    fn dtx_frames() {
+        let mut frames = Vec::new();
+        let internal = parse(&mut frames, &[TOC_CELT_20MS]).unwrap();
+
+        assert_eq!(frames, [[0u8; 0]]);
+        assert!(internal.is_dtx());
+
+        // empty frames in between and last.
+        let mut frames = Vec::new();
+        let internal = parse(&mut frames, &[TOC_CELT_20MS | 0x3, 0x84, 0, 2, 0, 1, 2]).unwrap();
+
+        assert_eq!(frames, [&[][..], &[1, 2][..], &[][..], &[][..]]);
+        assert_eq!(frames.iter().filter(|frame| Info::is_dtx_frame(frame)).count(), 3);
+        assert!(internal.has_dtx());
+        assert!(!internal.is_dtx());
+        assert_eq!(internal.num_dtx_frames, 3);
+    }
+
+    #[test]
+    fn strict_and_lenient() {
+        let mut packet = vec![TOC_CELT_20MS];
+        packet.resize(1 + 1276, 0x55);
+
+        let strict = ParseOptions::builder().strict(true).max_frame_bytes(1275).build();
+        let lenient = ParseOptions::builder().build();
+
+        assert_eq!(parse_with_options(&mut Vec::new(), &packet, &strict).err(), Some(Error::FrameTooBig));
+        assert!(parse_with_options(&mut Vec::new(), &packet, &lenient).is_ok());
+
+        // Code 3 without any frames.
+        let empty = [TOC_CELT_20MS | 0x3, 0x00];
+
+        assert_eq!(parse_with_options(&mut Vec::new(), &empty, &strict).err(), Some(Error::NoAudio));
+        assert!(parse_with_options(&mut Vec::new(), &empty, &lenient).is_ok());
+    }
+
+    #[test]
+    fn payload_and_overhead() {
+        // TOC, frame count, padding length, 2 frame lengths, 6 bytes of frames and
+        // 3 of padding.
+        let packet = [TOC_CELT_20MS | 0x3, 0xc3, 3, 1, 3, 1, 2, 3, 4, 5, 6, 0, 0, 0];
+        let internal = parse(&mut Vec::new(), &packet).unwrap();
+
+        assert_eq!(internal.payload_bytes, 6);
+        assert_eq!(internal.overhead_bytes(), 8);
+        assert_eq!(internal.padding.map(|(padding, _)| padding), Some(4));
+    }
+
+    #[test]
+    fn code1_even_length() {
+        let mut packet = vec![TOC_CELT_20MS | 0x1];
+        packet.extend(1..=10);
+
+        let mut frames = Vec::new();
+        parse(&mut frames, &packet).unwrap();
+
+        assert_eq!(frames, [[1, 2, 3, 4, 5], [6, 7, 8, 9, 10]]);
+
+        packet.pop();
+
+        assert_eq!(parse(&mut Vec::new(), &packet).err(), Some(Error::UnevenCode1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_code() {
+        assert_eq!(serde_json::to_string(&Code::Code3).unwrap(), "3");
+        assert_eq!(serde_json::from_str::<Code>("2").unwrap(), Code::Code2);
+        assert!(serde_json::from_str::<Code>("4").is_err());
+
+        let info = validate(&[TOC_CELT_20MS | 0x2, 1, 1, 2]).unwrap();
+        let value = serde_json::to_value(info).unwrap();
+
+        assert_eq!(value["toc"], TOC_CELT_20MS | 0x2);
+        assert_eq!(value["code_no"], 2);
+        assert_eq!(value["frame_config"]["config"]["framesize"], 20.0);
+        assert_eq!(serde_json::from_value::<Info>(value).unwrap(), info);
+    }
+
+    #[test]
+    fn code1_lone_toc() {
+        let mut frames = Vec::new();
+        let internal = parse(&mut frames, &[TOC_CELT_20MS | 0x1]).unwrap();
+
+        assert_eq!(frames, [[0u8; 0], [0u8; 0]]);
+        assert!(internal.is_dtx());
+
+        let mut frames = Vec::new();
+        parse(&mut frames, &[TOC_CELT_20MS | 0x1, 1, 2]).unwrap();
+
+        assert_eq!(frames, [[1], [2]]);
+    }
+
+    #[test]
+    fn arbitrary_input() {
+        let strict = ParseOptions::builder().strict(true).validate_padding(true).build();
+        let lenient = ParseOptions::builder().build();
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut buf = [0u8; 64];
+
+        for _ in 0..20000 {
+            // xorshift, cut to a random length below.
+            for byte in &mut buf {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                *byte = state as u8;
+            }
+
+            let packet = &buf[..(state >> 32) as usize % buf.len()];
+
+            let _ = parse_with_options(&mut Vec::new(), packet, &strict);
+            let _ = parse_with_options(&mut Vec::new(), packet, &lenient);
+            let _ = parse_self_delimiting(&mut Vec::new(), packet);
+            let _ = parse_toc(packet);
+            let _ = required_len(packet);
+            let _ = silk_header(packet);
+        }
+    }
+
+    #[test]
+    fn code2_asymmetric() {
+        let packet = [TOC_CELT_20MS | 0x2, 1, 0xaa, 0xbb, 0xcc, 0xdd];
+        let mut frames = Vec::new();
+
+        parse(&mut frames, &packet).unwrap();
+
+        assert_eq!(frames, [&[0xaa][..], &[0xbb, 0xcc, 0xdd][..]]);
+    }
+
+    #[test]
+    fn zero_frames() {
+        let lenient = ParseOptions::builder().build();
+        let mut frames = Vec::new();
+        let internal = parse_with_options(&mut frames, &[TOC_CELT_20MS | 0x3, 0x80], &lenient).unwrap();
+
+        assert!(frames.is_empty());
+        assert_eq!(internal.info.num_frames, 0);
+        assert_eq!(internal.info.duration_ms(), 0.0);
+        assert_eq!(internal.info.duration_samples(48000), 0);
+        assert_eq!(internal.bitrate_bps(), 0);
+    }
+
+    #[test]
+    fn padded_vbr_unequal_frames() {
+        // 300 bytes of padding (the length bytes 255 and 44 plus 254 + 44 trailing
+        // bytes), lengths of 2 and 300 bytes and the last frame of what is left.
+        let mut packet = vec![TOC_CELT_20MS | 0x3, 0xc3, 255, 44, 2, 252, 12];
+        packet.extend_from_slice(&[1, 2]);
+        packet.extend_from_slice(&[3; 300]);
+        packet.extend_from_slice(&[4; 5]);
+        packet.resize(packet.len() + 298, 0);
+
+        let mut frames = Vec::new();
+        let internal = parse(&mut frames, &packet).unwrap();
+
+        assert_eq!(frames, [&[1, 2][..], &[3; 300][..], &[4; 5][..]]);
+        assert_eq!(internal.padding.map(|(padding, _)| padding), Some(300));
+    }
+
+    #[test]
+    fn vbr_lengths_overrun() {
+        // lengths of 3 and 2 bytes with only 4 bytes left for frames.
+        let packet = [TOC_CELT_20MS | 0x3, 0x83, 3, 2, 1, 2, 3, 4];
+
+        assert_eq!(parse(&mut Vec::new(), &packet).err(), Some(Error::LengthOverflow));
+        assert_eq!(validate(&packet), Err(Error::LengthOverflow));
+    }
+
+    #[test]
+    fn info_as_key() {
+        use std::collections::HashMap;
+
+        let packets: [&[u8]; 4] = [
+            &[TOC_CELT_20MS, 1],
+            &[TOC_CELT_20MS, 2, 3],
+            &[TOC_CELT_20MS | 0x4, 1],
+            &[TOC_CELT_20MS | 0x1, 1, 2],
+        ];
+        let mut counts = HashMap::<Info, usize>::new();
+
+        for packet in packets {
+            *counts.entry(validate(packet).unwrap()).or_default() += 1;
+        }
+
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&validate(&[TOC_CELT_20MS]).unwrap()], 2);
+    }
+
+    #[test]
+    fn bitrate_of_20ms_packet() {
+        let mut packet = vec![TOC_CELT_20MS];
+        packet.resize(160, 0x55);
+
+        let internal = parse(&mut Vec::new(), &packet).unwrap();
+
+        // 160 bytes every 20 ms is 64 kbps, of which the TOC takes 0.4 kbps.
+        assert_eq!(internal.info.bitrate_bps(packet.len()), 64000);
+        assert_eq!(internal.bitrate_bps(), 63600);
+    }
+
+    #[test]
+    fn self_delimiting_stream() {
+        let buf = [TOC_CELT_20MS, 1, 0xff, TOC_CELT_20MS | 0x1, 1, 1, 2];
+        let packets = parse_stream(&buf).unwrap();
+
+        assert_eq!(packets.iter().map(|p| p.consumed).collect::<Vec<_>>(), [3, 4]);
+        assert_eq!(parse_stream(&buf[..6]).err(), Some(Error::LengthOverflow));
+    }
+
+    #[test]
+    fn length_widths() {
+        // lengths of 2 bytes in 1 byte and 300 bytes in 2 bytes, and the last frame.
+        let mut packet = vec![TOC_CELT_20MS | 0x3, 0x83, 2, 252, 12, 1, 2];
+        packet.extend_from_slice(&[3; 300]);
+        packet.push(4);
+
+        let mut frames = Vec::new();
+        let internal = parse_with_lengths(&mut frames, &packet).unwrap();
+
+        assert_eq!(frames, [(1, &[1, 2][..]), (2, &[3; 300][..]), (0, &[4][..])]);
+
+        // the TOC, frame count and length fields.
+        let num_length_bytes: usize = frames.iter().map(|&(width, _)| width as usize).sum();
+
+        assert_eq!(internal.overhead_bytes(), 2 + num_length_bytes);
+    }
+
+    #[test]
+    fn toc_only() {
+        let packets: [&[u8]; 5] = [
+            &[TOC_CELT_20MS, 1, 2],
+            &[TOC_CELT_20MS | 0x4 | 0x1, 1, 2],
+            &[TOC_CELT_20MS | 0x2, 1, 1, 2, 3],
+            &[TOC_CELT_20MS | 0x3, 0x03, 1, 2, 3],
+            &[TOC_CELT_20MS | 0x3, 0xc2, 1, 1, 2, 3, 0],
+        ];
+
+        for packet in packets {
+            assert_eq!(parse_toc(packet), Ok(parse(&mut Vec::new(), packet).unwrap().info));
+        }
+
+        assert_eq!(parse_toc(&[]), Err(Error::NoTOC));
+    }
+
+    #[test]
+    fn total_samples_of_packets() {
+        // 20 ms, 2 x 10 ms (SILK-only wide-band) and 3 x 2.5 ms (CELT-only
+        // full-band).
+        let packets: [&[u8]; 3] = [
+            &[TOC_CELT_20MS, 1],
+            &[8 << 3 | 0x1, 1, 2],
+            &[28 << 3 | 0x3, 0x03, 1, 2, 3],
+        ];
+
+        assert_eq!(total_samples(packets[..1].iter().copied(), 48000), Ok(960));
+        assert_eq!(total_samples(packets[..2].iter().copied(), 48000), Ok(1920));
+        assert_eq!(total_samples(packets.iter().copied(), 48000), Ok(2280));
+        assert_eq!(total_samples(packets.iter().copied(), 16000), Ok(760));
+        assert_eq!(total_samples([&[][..]].into_iter(), 48000), Err(Error::NoTOC));
+    }
+
+    #[test]
+    fn bitrate_of_padded_packet() {
+        // 2 x 20 ms CBR frames of 50 bytes, padded by 10 bytes.
+        let mut packet = vec![TOC_CELT_20MS | 0x3, 0x42, 9];
+        packet.extend_from_slice(&[0x55; 100]);
+        packet.resize(packet.len() + 9, 0);
+
+        let internal = parse(&mut Vec::new(), &packet).unwrap();
+
+        assert_eq!(internal.info.bitrate_bps(packet.len()), 112 * 8 * 25);
+        assert_eq!(internal.effective_bitrate_bps(), 102 * 8 * 25);
+        assert_eq!(internal.bitrate_bps(), 100 * 8 * 25);
+    }
+
+    #[test]
+    fn validate_strictly() {
+        let packet = [TOC_CELT_20MS | 0x3, 0x83, 1, 0, 1, 2, 3];
+        let info = validate(&packet).unwrap();
+
+        assert_eq!(info, parse(&mut Vec::new(), &packet).unwrap().info);
+
+        // strict regardless of the features.
+        assert_eq!(validate(&[TOC_CELT_20MS | 0x3, 0x80]), Err(Error::NoAudio));
+        assert_eq!(validate(&[TOC_CELT_20MS | 0x3, 0x07, 1, 2, 3, 4, 5, 6, 7]), Err(Error::TooMuchAudio));
+        assert_eq!(validate(&[TOC_CELT_20MS | 0x1, 1, 2, 3]), Err(Error::UnevenCode1));
+        assert_eq!(validate(&[]), Err(Error::NoTOC));
+    }
+
+    #[test]
+    fn display() {
+        let info = validate(&[TOC_CELT_20MS, 1]).unwrap();
+
+        assert_eq!(info.to_string(), "CELT/FB 20ms x1 mono CBR code0");
+
+        // SILK-only wide-band 20 ms stereo, three VBR frames.
+        let info = validate(&[9 << 3 | 0x4 | 0x3, 0x83, 1, 1, 1, 2, 3]).unwrap();
+
+        assert_eq!(info.to_string(), "SILK/WB 20ms x3 stereo VBR code3");
+
+        // CELT-only narrow-band 2.5 ms, unequal Code 2 frames.
+        let summary = validate(&[16 << 3 | 0x2, 1, 1, 2, 3]).unwrap().to_string();
+
+        assert!(summary.contains("2.5ms"));
+        assert!(summary.contains("VBR"));
+    }
+
+    #[test]
+    fn owned_frames() {
+        let packet = vec![TOC_CELT_20MS | 0x3, 0xc2, 1, 1, 1, 2, 0];
+        let mut frames = Vec::new();
+        let info = parse(&mut frames, &packet).unwrap().info;
+        let frames: Vec<Vec<u8>> = frames.into_iter().map(<[u8]>::to_vec).collect();
+
+        // outliving the packet.
+        let owned = parse_owned(&packet).unwrap();
+        drop(packet);
+
+        assert_eq!(owned, (info, frames, Some(vec![0])));
+    }
+
+    #[test]
+    fn bit_fields() {
+        // RFC 6716, Table 2: configurations in groups of mode and bandwidth.
+        let groups = [
+            (Mode::SILK, Bandwidth::Narrow, &[10.0, 20.0, 40.0, 60.0][..]),
+            (Mode::SILK, Bandwidth::Medium, &[10.0, 20.0, 40.0, 60.0]),
+            (Mode::SILK, Bandwidth::Wide, &[10.0, 20.0, 40.0, 60.0]),
+            (Mode::Hybrid, Bandwidth::SuperWide, &[10.0, 20.0]),
+            (Mode::Hybrid, Bandwidth::FullBand, &[10.0, 20.0]),
+            (Mode::CELT, Bandwidth::Narrow, &[2.5, 5.0, 10.0, 20.0]),
+            (Mode::CELT, Bandwidth::Wide, &[2.5, 5.0, 10.0, 20.0]),
+            (Mode::CELT, Bandwidth::SuperWide, &[2.5, 5.0, 10.0, 20.0]),
+            (Mode::CELT, Bandwidth::FullBand, &[2.5, 5.0, 10.0, 20.0]),
+        ];
+        let configs = groups
+            .iter()
+            .flat_map(|&(mode, bandwidth, framesizes)| framesizes.iter().map(move |&framesize| (mode, bandwidth, framesize)));
+        let codes = [Code::Code0, Code::Code1, Code::Code2, Code::Code3];
+
+        // RFC 6716, Figure 1: config in the upper 5 bits, then s and c.
+        for (config, (mode, bandwidth, framesize)) in configs.enumerate() {
+            for (s, is_stereo) in [(0, false), (1, true)] {
+                for (c, code) in codes.into_iter().enumerate() {
+                    let toc = (config * 8 + s * 4 + c) as u8;
+                    let info = parse_toc(&[toc, 0x01]).unwrap();
+
+                    assert_eq!(info.toc, toc);
+                    assert_eq!((info.mode(), info.bandwidth()), (mode, bandwidth));
+                    assert_eq!(info.frame_config.config.framesize(), framesize);
+                    assert_eq!(info.frame_config.is_stereo, is_stereo);
+                    assert_eq!(info.code_no, code);
+                }
+            }
+        }
+
+        // RFC 6716, Figure 5: v, p and then M in the lower 6 bits.
+        for v in 0..2 {
+            for p in 0..2 {
+                for m in 0..64 {
+                    let info = parse_toc(&[TOC_CELT_2_5MS | 0x3, (v * 128 + p * 64 + m) as u8]).unwrap();
+
+                    assert_eq!(info.num_frames, m);
+                    assert_eq!(info.is_vbr, Some(v == 1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn code_try_from() {
+        assert_eq!(Code::try_from(0), Ok(Code::Code0));
+        assert_eq!(Code::try_from(3), Ok(Code::Code3));
+        assert_eq!(Code::try_from(4), Err(Error::InvalidCode));
+        assert_eq!(Code::try_from(7), Err(Error::InvalidCode));
+        assert_eq!(Code::from_toc(7), Code::Code3);
+    }
+
+    /// SILK-only packets encoded by libopus, with the flags libopus decodes
+    /// from them noted along.
+    mod silk {
+        /// Wide-band 20 ms mono, with LBRR.
+        pub const MONO_20MS_LBRR: &[u8] = include_bytes!("../../tests/fixtures/silk/mono-20ms-lbrr.bin");
+        /// Medium-band 20 ms mono, without LBRR.
+        pub const MONO_20MS: &[u8] = include_bytes!("../../tests/fixtures/silk/mono-20ms.bin");
+        /// Wide-band 20 ms stereo, VAD of 1/0 and LBRR of the mid channel only.
+        pub const STEREO_20MS_LBRR: &[u8] = include_bytes!("../../tests/fixtures/silk/stereo-20ms-lbrr.bin");
+        /// Wide-band 20 ms stereo, VAD of 1/1 without LBRR.
+        pub const STEREO_20MS: &[u8] = include_bytes!("../../tests/fixtures/silk/stereo-20ms.bin");
+        /// Wide-band 20 ms stereo, VAD of 1/0 without LBRR, only the mid channel coded.
+        pub const STEREO_20MS_MID_ONLY: &[u8] = include_bytes!("../../tests/fixtures/silk/stereo-20ms-mid-only.bin");
+        /// Wide-band 60 ms stereo, VAD of 110/110 without LBRR.
+        pub const STEREO_60MS: &[u8] = include_bytes!("../../tests/fixtures/silk/stereo-60ms.bin");
+        /// Wide-band 60 ms stereo, VAD of 111/000 without LBRR, only the mid channel
+        /// coded.
+        pub const STEREO_60MS_MID_ONLY: &[u8] = include_bytes!("../../tests/fixtures/silk/stereo-60ms-mid-only.bin");
+    }
+
+    #[test]
+    fn fec() {
+        assert_eq!(has_fec(silk::MONO_20MS_LBRR), Ok(true));
+        assert_eq!(has_fec(silk::MONO_20MS), Ok(false));
+        assert_eq!(has_fec(silk::STEREO_20MS_LBRR), Ok(true));
+        assert_eq!(has_fec(silk::STEREO_20MS), Ok(false));
+
+        // CELT-only and an empty (DTX) SILK-only wide-band 20 ms frame.
+        assert_eq!(has_fec(&[TOC_CELT_20MS, 0xff, 0xfe]), Ok(false));
+        assert_eq!(has_fec(&[9 << 3]), Ok(false));
+        assert_eq!(has_fec(&[]), Err(Error::NoTOC));
+    }
+
+    #[test]
+    fn silk_headers() {
+        let headers = [
+            (silk::MONO_20MS_LBRR, 1, [[true, false, false], [false; 3]], [true, false], None),
+            (silk::STEREO_20MS_LBRR, 1, [[true, false, false], [false; 3]], [true, false], None),
+            (silk::STEREO_20MS, 1, [[true, false, false], [true, false, false]], [false; 2], Some(false)),
+            (silk::STEREO_20MS_MID_ONLY, 1, [[true, false, false], [false; 3]], [false; 2], Some(true)),
+            (silk::STEREO_60MS, 3, [[true, true, false], [true, true, false]], [false; 2], Some(false)),
+            (silk::STEREO_60MS_MID_ONLY, 3, [[true; 3], [false; 3]], [false; 2], Some(true)),
+        ];
+
+        for (packet, num_silk_frames, vad, lbrr, mid_only) in headers {
+            assert_eq!(silk_header(packet), Ok(SilkHeader { num_silk_frames, vad, lbrr, mid_only }));
+        }
+
+        assert_eq!(silk_header(&[TOC_CELT_20MS, 0xff, 0xfe]), Err(Error::NoSilk));
+        assert_eq!(silk_header(&[9 << 3]), Err(Error::NoSilk));
+    }
+
+
+    #[test]
+    fn required_lengths() {
+        let cases: [(&[u8], usize); 7] = [
+            // all frame lengths implied by the packet length.
+            (&[TOC_CELT_20MS], 1),
+            (&[TOC_CELT_20MS | 0x1], 1),
+            (&[TOC_CELT_20MS | 0x3, 0x03], 2),
+            // frame 0 of 10 bytes, then of 252 + 2 * 4 bytes.
+            (&[TOC_CELT_20MS | 0x2, 10], 2 + 10),
+            (&[TOC_CELT_20MS | 0x2, 252, 2], 3 + 260),
+            // VBR frames of 5 and 260 bytes, the last frame implied.
+            (&[TOC_CELT_20MS | 0x3, 0x83, 5, 252, 2], 5 + 5 + 260),
+            // padding of 254 + 254 + 10 bytes, excluding the padding length bytes.
+            (&[TOC_CELT_20MS | 0x3, 0x41, 255, 255, 10], 5 + 518),
+        ];
+
+        for (header, len) in cases {
+            assert_eq!(required_len(header), Ok(len));
+
+            // the last frame may be empty, but nothing may be missing.
+            let mut packet = header.to_vec();
+            packet.resize(len, 0);
+
+            assert!(parse(&mut Vec::new(), &packet).is_ok());
+            assert!(parse(&mut Vec::new(), &packet[..len - 1]).is_err());
+        }
+
+        let truncated: [&[u8]; 6] = [
+            &[TOC_CELT_20MS | 0x2],
+            &[TOC_CELT_20MS | 0x2, 252],
+            &[TOC_CELT_20MS | 0x3],
+            &[TOC_CELT_20MS | 0x3, 0x41, 255],
+            &[TOC_CELT_20MS | 0x3, 0x83, 5],
+            &[TOC_CELT_20MS | 0x3, 0x83, 5, 252],
+        ];
+
+        for packet in truncated {
+            assert_eq!(required_len(packet), Err(Error::PacketTooSmall));
+        }
+
+        assert_eq!(required_len(&[]), Err(Error::NoTOC));
+    }
 }
@@ -0,0 +1,138 @@
+//! Minimal range decoder, enough to read the leading symbols of a frame (e.g.
+//! the SILK frame header) without decoding any audio.
+//! 
+//! See [RFC 6716, Section 4.1][1].
+//! 
+//! [1]: https://datatracker.ietf.org/doc/html/rfc6716#section-4.1
+
+/// Number of bits to output at a time.
+const SYM_BITS: u32 = 8;
+/// Number of bits in the state registers.
+const CODE_BITS: u32 = 32;
+/// Maximum symbol value.
+const SYM_MAX: u32 = (1 << SYM_BITS) - 1;
+/// Bits of the first byte not fitting the initial range.
+const CODE_EXTRA: u32 = (CODE_BITS - 2) % SYM_BITS + 1;
+/// Top of the range.
+const CODE_TOP: u32 = 1 << (CODE_BITS - 1);
+/// Bottom of the range, the range is renormalized below it.
+const CODE_BOT: u32 = CODE_TOP >> SYM_BITS;
+
+pub(crate) struct RangeDecoder<'a> {
+    data: &'a [u8],
+    offs: usize,
+    /// Size of the current range.
+    rng: u32,
+    /// Difference between the top of the current range and the coded value
+    /// minus one.
+    val: u32,
+    /// Last byte read, whose bits are partially consumed.
+    rem: u32,
+}
+
+impl<'a> RangeDecoder<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        let mut dec = Self { data, offs: 0, rng: 1 << CODE_EXTRA, val: 0, rem: 0 };
+
+        dec.rem = dec.read_byte();
+        dec.val = dec.rng - 1 - (dec.rem >> (SYM_BITS - CODE_EXTRA));
+        dec.normalize();
+
+        dec
+    }
+
+    /// Reads the next byte, zero past the end of data as the encoder may have
+    /// omitted trailing zeros.
+    fn read_byte(&mut self) -> u32 {
+        let byte = self.data.get(self.offs).map_or(0, |&byte| byte as u32);
+
+        self.offs += 1;
+
+        byte
+    }
+
+    fn normalize(&mut self) {
+        while self.rng <= CODE_BOT {
+            self.rng <<= SYM_BITS;
+
+            let sym = self.rem;
+
+            self.rem = self.read_byte();
+
+            let sym = (sym << SYM_BITS | self.rem) >> (SYM_BITS - CODE_EXTRA);
+
+            self.val = ((self.val << SYM_BITS) + (SYM_MAX & !sym)) & (CODE_TOP - 1);
+        }
+    }
+
+    /// Decodes a bit whose probability of being set is `1/2**logp`.
+    pub(crate) fn decode_bit_logp(&mut self, logp: u32) -> bool {
+        let s = self.rng >> logp;
+        let bit = self.val < s;
+
+        if bit {
+            self.rng = s;
+        } else {
+            self.val -= s;
+            self.rng -= s;
+        }
+
+        self.normalize();
+
+        bit
+    }
+
+    /// Decodes a symbol with an inverse cumulative distribution table `icdf`
+    /// scaled to `2**ftb`, terminated by zero.
+    pub(crate) fn decode_icdf(&mut self, icdf: &[u8], ftb: u32) -> usize {
+        let r = self.rng >> ftb;
+        let mut s = self.rng;
+        let mut t;
+        let mut sym = 0;
+
+        loop {
+            t = s;
+            s = r * icdf[sym] as u32;
+
+            if self.val >= s {
+                break;
+            }
+
+            sym += 1;
+        }
+
+        self.val -= s;
+        self.rng = t - s;
+        self.normalize();
+
+        sym
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RangeDecoder;
+
+    // streams encoded by the range encoder of libopus (`ec_enc_bit_logp` and
+    // `ec_enc_icdf`).
+
+    #[test]
+    fn decode_bit_logp() {
+        let bits = [(true, 1), (false, 1), (false, 2), (true, 2), (false, 8), (true, 8), (true, 1), (false, 15), (true, 3), (false, 1)];
+        let mut dec = RangeDecoder::new(&[0xaf, 0xf3, 0x60]);
+
+        for (bit, logp) in bits {
+            assert_eq!(dec.decode_bit_logp(logp), bit);
+        }
+    }
+
+    #[test]
+    fn decode_icdf() {
+        let icdf = [192, 96, 32, 0];
+        let mut dec = RangeDecoder::new(&[0x3c, 0x63, 0x00]);
+
+        for sym in [0, 3, 1, 2, 2, 0, 3, 1] {
+            assert_eq!(dec.decode_icdf(&icdf, 8), sym);
+        }
+    }
+}
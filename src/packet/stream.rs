@@ -0,0 +1,179 @@
+//! Parsing of a stream of concatenated self-delimiting packets read from a
+//! [`Read`] source, holding no more than a packet in memory.
+
+use std::fmt;
+use std::io::{self, Read};
+
+use super::parser::{parse_self_delimiting, required_len_impl, Error, Info};
+
+/// Bytes read from the source at once.
+const CHUNK_LEN: usize = 1024;
+
+/// Information and frames of a packet.
+pub type StreamPacket = (Info, Vec<Vec<u8>>);
+
+/// An error that occured while reading the stream.
+#[derive(Debug)]
+pub enum StreamError {
+    /// Reading the underlying source failed.
+    Io(io::Error),
+    /// A packet is malformed.
+    Parse(Error),
+    /// Stream ended in the middle of a packet.
+    Truncated,
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(err) => write!(f, "failed to read the stream: {}", err),
+            StreamError::Parse(err) => write!(f, "packet is malformed: {}", err),
+            StreamError::Truncated => f.write_str("stream ends in the middle of a packet")
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(err: io::Error) -> Self {
+        StreamError::Io(err)
+    }
+}
+
+impl From<Error> for StreamError {
+    fn from(err: Error) -> Self {
+        StreamError::Parse(err)
+    }
+}
+
+/// Iterator over the self-delimiting packets of a byte stream, yielding the
+/// information and frames of each.
+/// 
+/// The length fields of a packet are read first to learn its whole length
+/// before reading its body. Iteration stops after the first error, since the
+/// bounds of the following packets are unknown then.
+pub struct StreamParser<R: Read> {
+    reader: R,
+    /// Bytes read but not yet parsed.
+    buffer: Vec<u8>,
+    failed: bool,
+}
+
+impl<R: Read> StreamParser<R> {
+    /// Parses the packets read from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self { reader, buffer: Vec::new(), failed: false }
+    }
+
+    /// Reads a chunk of the stream into the buffer, returning `false` at
+    /// the end of the stream.
+    fn fill(&mut self) -> Result<bool, StreamError> {
+        let mut chunk = [0; CHUNK_LEN];
+
+        let len = loop {
+            match self.reader.read(&mut chunk) {
+                Ok(len) => break len,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into())
+            }
+        };
+
+        self.buffer.extend_from_slice(&chunk[..len]);
+
+        Ok(len > 0)
+    }
+
+    fn read_packet(&mut self) -> Result<Option<StreamPacket>, StreamError> {
+        loop {
+            if !self.buffer.is_empty() {
+                match required_len_impl(&self.buffer, true) {
+                    Ok(len) if len <= self.buffer.len() => break,
+                    // headers or body are incomplete.
+                    Ok(_) | Err(Error::PacketTooSmall) => {},
+                    Err(err) => return Err(err.into())
+                }
+            }
+
+            if !self.fill()? {
+                return if self.buffer.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(StreamError::Truncated)
+                };
+            }
+        }
+
+        let mut frames = Vec::new();
+        let internal = parse_self_delimiting(&mut frames, &self.buffer)?;
+        let frames = frames.into_iter().map(<[u8]>::to_vec).collect();
+        let (info, consumed) = (internal.info, internal.consumed);
+
+        self.buffer.drain(..consumed);
+
+        Ok(Some((info, frames)))
+    }
+}
+
+impl<R: Read> Iterator for StreamParser<R> {
+    type Item = Result<StreamPacket, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let packet = self.read_packet().transpose();
+
+        self.failed = matches!(packet, Some(Err(_)));
+
+        packet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::packet::parser::Code;
+
+    /// CELT-only full-band 20 ms, mono.
+    const TOC_CELT_20MS: u8 = 31 << 3;
+
+    /// Self-delimiting packets, the second straddling the chunks read.
+    fn stream() -> Vec<u8> {
+        let mut stream = vec![TOC_CELT_20MS, 2, 1, 2];
+
+        // 1000 = 252 + 187 * 4.
+        stream.extend_from_slice(&[TOC_CELT_20MS | 0x2, 252, 187, 1]);
+        stream.extend_from_slice(&[3; 1000]);
+        stream.push(4);
+        stream.extend_from_slice(&[TOC_CELT_20MS | 0x3, 0x02, 1, 5, 6]);
+        stream
+    }
+
+    #[test]
+    fn concatenated_packets() {
+        let packets = StreamParser::new(Cursor::new(stream()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[0].1, [vec![1, 2]]);
+        assert_eq!(packets[1].0.code_no, Code::Code2);
+        assert_eq!(packets[1].1, [vec![3; 1000], vec![4]]);
+        assert_eq!(packets[2].1, [vec![5], vec![6]]);
+    }
+
+    #[test]
+    fn truncated_stream() {
+        let stream = stream();
+        let mut parser = StreamParser::new(Cursor::new(&stream[..stream.len() - 1]));
+
+        assert!(parser.next().unwrap().is_ok());
+        assert!(parser.next().unwrap().is_ok());
+        assert!(matches!(parser.next(), Some(Err(StreamError::Truncated))));
+        assert!(parser.next().is_none());
+    }
+}
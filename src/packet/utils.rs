@@ -1,13 +1,13 @@
 // https://datatracker.ietf.org/doc/html/rfc6716#section-3.2.1
 pub(crate) fn parse_frame_length(bytes: &[u8]) -> Option<(usize, usize)> {
-    if bytes.len() < 1 {
+    if bytes.is_empty() {
         return None;
     }
 
     let mut length = bytes[0] as usize;
     
     if length > 251 {
-        if bytes.len() < 2 {
+        if bytes.len() >= 2 {
             length += bytes[1] as usize * 4;
 
             Some((length, 2))
@@ -17,4 +17,20 @@ pub(crate) fn parse_frame_length(bytes: &[u8]) -> Option<(usize, usize)> {
     } else {
         Some((length, 1))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_frame_length;
+
+    #[test]
+    fn frame_length_boundaries() {
+        assert_eq!(parse_frame_length(&[]), None);
+        assert_eq!(parse_frame_length(&[0]), Some((0, 1)));
+        assert_eq!(parse_frame_length(&[251, 1]), Some((251, 1)));
+        assert_eq!(parse_frame_length(&[252]), None);
+        assert_eq!(parse_frame_length(&[252, 0]), Some((252, 2)));
+        assert_eq!(parse_frame_length(&[252, 1]), Some((256, 2)));
+        assert_eq!(parse_frame_length(&[255, 255]), Some((1275, 2)));
+    }
+}
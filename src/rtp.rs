@@ -0,0 +1,56 @@
+//! Helpers for Opus carried over RTP, where the RTP payload is a bare
+//! non-self-delimiting Opus packet and the RTP timestamp advances with a 48 kHz
+//! clock regardless of the coded bandwidth.
+//! 
+//! See [RFC 7587][1].
+//! 
+//! [1]: https://datatracker.ietf.org/doc/html/rfc7587
+
+use crate::packet::parser::{Error, FrameIter, Info};
+
+/// RTP clock rate of Opus streams.
+pub const CLOCK_RATE: u32 = 48000;
+
+/// Number of 48 kHz clock samples the packet (RTP payload) spans.
+/// 
+/// In DTX, a packet with a TOC but no frame data signals silence spanning the
+/// configured frame duration, which is counted as any other frame. A payload
+/// without even the TOC is rejected with [`Error::NoTOC`], as its duration is
+/// unknown.
+pub fn packet_samples(packet: &[u8]) -> Result<u32, Error> {
+    let info = FrameIter::new(packet)?.info();
+
+    Ok(info.duration_samples(CLOCK_RATE) as u32)
+}
+
+/// Opus packet carried by an RTP payload, which is the payload itself as Opus
+/// adds no framing of its own over RTP.
+pub fn opus_from_rtp(rtp_payload: &[u8]) -> &[u8] {
+    rtp_payload
+}
+
+/// RTP timestamp increment from the packet to the next at `clock` Hz, e.g.
+/// 960 for a 20 ms packet at [`CLOCK_RATE`].
+pub fn rtp_timestamp_increment(info: &Info, clock: u32) -> u32 {
+    info.duration_samples(clock) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_increment() {
+        // CELT-only full-band 20 ms, and the same in DTX (no frame data).
+        let payload = [31 << 3, 0xff, 0xfe];
+
+        assert_eq!(packet_samples(opus_from_rtp(&payload)), Ok(960));
+        assert_eq!(packet_samples(&payload[..1]), Ok(960));
+        assert_eq!(packet_samples(&[]), Err(Error::NoTOC));
+
+        // SILK-only narrow-band 60 ms, twice.
+        let info = FrameIter::new(&[3 << 3 | 0x1]).unwrap().info();
+
+        assert_eq!(rtp_timestamp_increment(&info, CLOCK_RATE), 5760);
+    }
+}
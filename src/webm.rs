@@ -0,0 +1,466 @@
+//! Minimal WebM (Matroska) demuxer for Opus tracks, extracting Opus packets
+//! out of `SimpleBlock` and `Block` elements so they can be handed to the
+//! [parser](crate::packet::parser) without a third-party demuxer.
+//!
+//! Only the first Opus track is demuxed, blocks of any other track are skipped.
+//! Elements are read in stream order without seeking, so the tracks must be
+//! described before the first cluster, as muxers do.
+//!
+//! See [RFC 8794][1] for the EBML encoding and the [Matroska specification][2]
+//! for the elements and block lacing.
+//!
+//! [1]: https://datatracker.ietf.org/doc/html/rfc8794
+//! [2]: https://www.matroska.org/technical/elements.html
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Read};
+
+use crate::header::{self, OpusHead};
+
+/// Segment, the top-level element holding everything else.
+const SEGMENT: u32 = 0x18538067;
+/// Tracks, the description of all the tracks.
+const TRACKS: u32 = 0x1654AE6B;
+/// Track entry, the description of a track.
+const TRACK_ENTRY: u32 = 0xAE;
+/// Track number, as referred to by blocks.
+const TRACK_NUMBER: u32 = 0xD7;
+/// Codec identifier of a track.
+const CODEC_ID: u32 = 0x86;
+/// Codec initialization data of a track, the `OpusHead` for Opus.
+const CODEC_PRIVATE: u32 = 0x63A2;
+/// Cluster, a group of blocks.
+const CLUSTER: u32 = 0x1F43B675;
+/// Block group, a block with additional information.
+const BLOCK_GROUP: u32 = 0xA0;
+/// Block, within a block group.
+const BLOCK: u32 = 0xA1;
+/// Block without additional information.
+const SIMPLE_BLOCK: u32 = 0xA3;
+
+/// Codec identifier of Opus.
+const CODEC_OPUS: &[u8] = b"A_OPUS";
+
+/// An error that occured during demuxing.
+#[derive(Debug)]
+pub enum WebmError {
+    /// Reading the underlying source failed.
+    Io(io::Error),
+    /// Stream ended in the middle of an element.
+    Truncated,
+    /// Element has a malformed ID or size.
+    BadElement,
+    /// Element of unknown size is neither the segment nor a cluster.
+    UnknownSize,
+    /// Block is malformed or its lacing is inconsistent with its size.
+    BadBlock,
+    /// No Opus track exists.
+    NoOpusTrack,
+    /// `OpusHead` (codec private data) is malformed.
+    Head(header::Error),
+}
+
+impl fmt::Display for WebmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebmError::Io(err) => write!(f, "failed to read the stream: {}", err),
+            WebmError::Truncated => f.write_str("stream is truncated"),
+            WebmError::BadElement => f.write_str("element is malformed"),
+            WebmError::UnknownSize => f.write_str("element has an unsupported unknown size"),
+            WebmError::BadBlock => f.write_str("block is malformed"),
+            WebmError::NoOpusTrack => f.write_str("stream has no Opus track"),
+            WebmError::Head(err) => write!(f, "OpusHead is malformed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for WebmError {}
+
+impl From<header::Error> for WebmError {
+    fn from(err: header::Error) -> Self {
+        WebmError::Head(err)
+    }
+}
+
+impl From<io::Error> for WebmError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => WebmError::Truncated,
+            _ => WebmError::Io(err)
+        }
+    }
+}
+
+/// Element of interest to the demuxer.
+enum Element {
+    Cluster,
+    /// Data of a (simple) block.
+    Block(Vec<u8>),
+}
+
+/// Description of a track.
+#[derive(Default)]
+struct Track {
+    number: u64,
+    codec: Vec<u8>,
+    private: Vec<u8>,
+}
+
+/// Reader of the Opus packets of a WebM stream.
+///
+/// The tracks are read upon construction, iterating yields the packets of the
+/// Opus track that follow, with laced blocks split into their packets.
+/// Iteration stops after the first error.
+pub struct WebmReader<R: Read> {
+    reader: R,
+    /// Number of the demuxed track.
+    track: u64,
+    head: Option<OpusHead>,
+    /// Packets of the last read block.
+    packets: VecDeque<Vec<u8>>,
+    failed: bool,
+}
+
+impl<R: Read> WebmReader<R> {
+    /// Reads the tracks of `reader`, upto the first cluster.
+    pub fn new(reader: R) -> Result<Self, WebmError> {
+        let mut webm = Self {
+            reader,
+            track: 0,
+            head: None,
+            packets: VecDeque::new(),
+            failed: false
+        };
+
+        let mut tracks = Vec::new();
+
+        // elements are read upto the first cluster, blocks must not precede it.
+        if let Some(Element::Block(_)) = webm.read_element(&mut tracks)? {
+            return Err(WebmError::BadBlock);
+        }
+
+        let track = tracks
+            .into_iter()
+            .find(|track| track.codec == CODEC_OPUS)
+            .ok_or(WebmError::NoOpusTrack)?;
+
+        webm.track = track.number;
+
+        if !track.private.is_empty() {
+            webm.head = Some(OpusHead::parse(&track.private)?);
+        }
+
+        Ok(webm)
+    }
+
+    /// Identification header of the Opus track, if the track carries it.
+    pub fn head(&self) -> Option<&OpusHead> {
+        self.head.as_ref()
+    }
+
+    /// Reads the next element of interest, descending into master elements and
+    /// recording track descriptions in `tracks` along the way. [`None`] at the
+    /// end of the stream.
+    fn read_element(&mut self, tracks: &mut Vec<Track>) -> Result<Option<Element>, WebmError> {
+        loop {
+            let (id, size) = match self.read_header()? {
+                Some(header) => header,
+                None => return Ok(None)
+            };
+
+            // children of master elements simply follow their header.
+            match id {
+                SEGMENT | TRACKS | BLOCK_GROUP => continue,
+                CLUSTER => return Ok(Some(Element::Cluster)),
+                TRACK_ENTRY => {
+                    tracks.push(Track::default());
+                    continue;
+                }
+                _ => {}
+            }
+
+            let size = size.ok_or(WebmError::UnknownSize)?;
+
+            match (id, tracks.last_mut()) {
+                (SIMPLE_BLOCK | BLOCK, _) => return Ok(Some(Element::Block(self.read_data(size)?))),
+
+                (TRACK_NUMBER, Some(track)) => {
+                    track.number = self.read_data(size)?
+                        .iter()
+                        .fold(0, |number, &byte| number << 8 | byte as u64);
+                }
+
+                (CODEC_ID, Some(track)) => track.codec = self.read_data(size)?,
+
+                (CODEC_PRIVATE, Some(track)) => track.private = self.read_data(size)?,
+
+                _ => {
+                    let skipped = io::copy(&mut self.reader.by_ref().take(size), &mut io::sink())?;
+
+                    if skipped < size {
+                        return Err(WebmError::Truncated);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reads the ID and size of the next element, the size is [`None`] if unknown.
+    fn read_header(&mut self) -> Result<Option<(u32, Option<u64>)>, WebmError> {
+        let mut byte = [0];
+
+        // a stream may only end between elements.
+        if self.reader.read(&mut byte)? == 0 {
+            return Ok(None);
+        }
+
+        // IDs keep their length marker and are at most 4 bytes long.
+        let len = byte[0].leading_zeros() as usize + 1;
+
+        if len > 4 {
+            return Err(WebmError::BadElement);
+        }
+
+        let mut id = byte[0] as u32;
+
+        for _ in 1..len {
+            self.reader.read_exact(&mut byte)?;
+            id = id << 8 | byte[0] as u32;
+        }
+
+        self.reader.read_exact(&mut byte)?;
+
+        let len = byte[0].leading_zeros() + 1;
+
+        if len > 8 {
+            return Err(WebmError::BadElement);
+        }
+
+        let mut size = (byte[0] as u64) & (0xFF >> len);
+
+        for _ in 1..len {
+            self.reader.read_exact(&mut byte)?;
+            size = size << 8 | byte[0] as u64;
+        }
+
+        // all ones denote an unknown size.
+        if size == (1 << (7 * len)) - 1 {
+            return Ok(Some((id, None)));
+        }
+
+        Ok(Some((id, Some(size))))
+    }
+
+    /// Reads the data of an element of `size` bytes.
+    fn read_data(&mut self, size: u64) -> Result<Vec<u8>, WebmError> {
+        let mut data = Vec::new();
+
+        self.reader.by_ref().take(size).read_to_end(&mut data)?;
+
+        if (data.len() as u64) < size {
+            return Err(WebmError::Truncated);
+        }
+
+        Ok(data)
+    }
+
+    /// Splits the packets of the demuxed track out of a block.
+    fn split_block(&mut self, block: &[u8]) -> Result<(), WebmError> {
+        //  0                   1                   2                   3
+        //  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+        // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+        // | track number (vint)   | timestamp                     | flags |
+        // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+        // | lace count (if laced) | lace sizes (if laced) ...             |
+        // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+        let (track, len) = read_vint(block).ok_or(WebmError::BadBlock)?;
+
+        if track != self.track {
+            return Ok(());
+        }
+
+        let flags = *block.get(len + 2).ok_or(WebmError::BadBlock)?;
+        let mut data = &block[len + 3..];
+
+        let lacing = (flags >> 1) & 0x3;
+
+        if lacing == 0 {
+            self.packets.push_back(data.to_vec());
+            return Ok(());
+        }
+
+        let (&num_laced, rest) = data.split_first().ok_or(WebmError::BadBlock)?;
+        let num_laced = num_laced as usize + 1;
+
+        data = rest;
+
+        let mut sizes = Vec::with_capacity(num_laced);
+
+        match lacing {
+            // Xiph lacing, sizes are sums of bytes upto the first non-255 byte.
+            0x1 => {
+                for _ in 1..num_laced {
+                    let mut size = 0;
+
+                    loop {
+                        let (&byte, rest) = data.split_first().ok_or(WebmError::BadBlock)?;
+
+                        data = rest;
+                        size += byte as usize;
+
+                        if byte != 255 {
+                            break;
+                        }
+                    }
+
+                    sizes.push(size);
+                }
+            }
+
+            // fixed-size lacing, the data is split evenly.
+            0x2 => {
+                if !data.len().is_multiple_of(num_laced) {
+                    return Err(WebmError::BadBlock);
+                }
+
+                sizes.resize(num_laced - 1, data.len() / num_laced);
+            }
+
+            // EBML lacing, the first size is followed by signed differences.
+            _ => {
+                let mut size = 0;
+
+                for i in 1..num_laced {
+                    let (value, len) = read_vint(data).ok_or(WebmError::BadBlock)?;
+
+                    data = &data[len..];
+
+                    size = if i == 1 {
+                        value as i64
+                    } else {
+                        size + value as i64 - ((1 << (7 * len - 1)) - 1)
+                    };
+
+                    sizes.push(usize::try_from(size).map_err(|_| WebmError::BadBlock)?);
+                }
+            }
+        }
+
+        for size in sizes {
+            if size > data.len() {
+                return Err(WebmError::BadBlock);
+            }
+
+            let (packet, rest) = data.split_at(size);
+
+            self.packets.push_back(packet.to_vec());
+            data = rest;
+        }
+
+        // remaining data belongs to the last packet.
+        self.packets.push_back(data.to_vec());
+
+        Ok(())
+    }
+
+    fn read_packet(&mut self) -> Result<Option<Vec<u8>>, WebmError> {
+        loop {
+            if let Some(packet) = self.packets.pop_front() {
+                return Ok(Some(packet));
+            }
+
+            // tracks after the first cluster are of no use.
+            match self.read_element(&mut Vec::new())? {
+                Some(Element::Block(block)) => self.split_block(&block)?,
+                Some(Element::Cluster) => {},
+                None => return Ok(None)
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for WebmReader<R> {
+    type Item = Result<Vec<u8>, WebmError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let packet = self.read_packet().transpose();
+
+        self.failed = matches!(packet, Some(Err(_)));
+
+        packet
+    }
+}
+
+/// Reads a variable-length integer with its length marker removed, returning
+/// the integer and its length.
+fn read_vint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let first = *bytes.first()?;
+    let len = first.leading_zeros() as usize + 1;
+
+    if len > 8 {
+        return None;
+    }
+
+    let value = bytes.get(1..len)?
+        .iter()
+        .fold((first as u64) & (0xFF >> len), |value, &byte| value << 8 | byte as u64);
+
+    Some((value, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::parser::{parse, Bandwidth, Mode};
+
+    const SAMPLE: &[u8] = include_bytes!("../tests/fixtures/sample.webm");
+
+    #[test]
+    fn demux_fixture() {
+        let reader = WebmReader::new(SAMPLE).unwrap();
+
+        assert_eq!(reader.head().unwrap().pre_skip, 312);
+
+        // one unlaced, three Xiph, three EBML and two fixed laced packets, and
+        // one of a block group, skipping the video track.
+        let packets = reader.collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(packets.len(), 10);
+        assert_eq!(packets[0], [0xf8, 0xff, 0xfe]);
+
+        let info = parse(&mut Vec::new(), &packets[0]).unwrap().info;
+
+        assert_eq!(info.mode(), Mode::CELT);
+        assert_eq!(info.bandwidth(), Bandwidth::FullBand);
+        assert_eq!(info.duration_ms(), 20.0);
+    }
+
+    #[test]
+    fn truncated_fixture() {
+        let reader = WebmReader::new(&SAMPLE[..SAMPLE.len() - 2]).unwrap();
+        let last = reader.last().unwrap();
+
+        assert!(matches!(last, Err(WebmError::Truncated)));
+    }
+
+    #[test]
+    fn corrupt_element() {
+        let mut sample = SAMPLE.to_vec();
+
+        // the ID of the element following the first (unlaced) block, a zero
+        // byte being no valid vint.
+        let block = sample.windows(2).position(|bytes| bytes == [SIMPLE_BLOCK as u8, 0x87]).unwrap();
+        sample[block + 2 + 7] = 0x00;
+
+        let mut reader = WebmReader::new(&sample[..]).unwrap();
+
+        assert_eq!(reader.next().unwrap().unwrap(), [0xf8, 0xff, 0xfe]);
+        assert!(matches!(reader.next(), Some(Err(WebmError::BadElement))));
+        assert!(reader.next().is_none());
+    }
+}
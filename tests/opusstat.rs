@@ -0,0 +1,25 @@
+#![cfg(feature = "tools")]
+
+use std::process::Command;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sample.opus");
+
+#[test]
+fn summary_totals() {
+    let output = Command::new(env!("CARGO_BIN_EXE_opusstat"))
+        .args(["--summary", FIXTURE])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+
+    // header, a row per distinct Info (Code 0, 1, 2, CBR and VBR Code 3) and totals.
+    assert_eq!(lines.len(), 7);
+
+    let total: Vec<_> = lines[6].split_whitespace().collect();
+
+    assert_eq!(total, ["total", "11", "19", "654", "360", "14.5"]);
+}